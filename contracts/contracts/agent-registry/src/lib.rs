@@ -1,6 +1,6 @@
 #![no_std]
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype, symbol_short, Address, Env, Symbol,
+    contract, contracterror, contractevent, contractimpl, contracttype, Address, Env, String, Vec,
 };
 
 #[contracterror]
@@ -11,25 +11,73 @@ pub enum Error {
     NotInitialized = 2,
     Unauthorized = 3,
     InvalidScope = 4,
+    AlreadyRegistered = 5,
+    AgentNotFound = 6,
 }
 
-
 #[contracttype]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[repr(u32)]
 pub enum Scope {
-    Renewals = 1 << 0,
-    GiftCards = 1 << 1,
-    Approvals = 1 << 2,
+    Renewals = 1,
+    GiftCards = 2,
+    Approvals = 4,
+}
+
+/// Bitmask of every defined `Scope`.
+pub const ALL_SCOPES: u32 =
+    Scope::Renewals as u32 | Scope::GiftCards as u32 | Scope::Approvals as u32;
+
+/// Upper bound on the page size `list_agents` returns.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
+/// Everything the registry knows about an agent.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AgentRecord {
+    /// Bitmask of granted `Scope`s.
+    pub scopes: u32,
+    pub registered_at: u64,
+    /// Timestamp after which the agent is no longer authorized; 0 never expires.
+    pub expires_at: u64,
+    pub label: String,
+    pub registered_by: Address,
+}
+
+#[contractevent(topics = ["agent", "reg"], data_format = "single-value")]
+pub struct AgentRegistered {
+    pub agent: Address,
 }
 
+#[contractevent(topics = ["agent", "scopes"], data_format = "vec")]
+pub struct AgentScopesUpdated {
+    pub agent: Address,
+    pub scopes: u32,
+}
 
+#[contractevent(topics = ["agent", "update"], data_format = "vec")]
+pub struct AgentUpdated {
+    pub agent: Address,
+    pub label: String,
+    pub expires_at: u64,
+}
+
+#[contractevent(topics = ["agent", "revoke"], data_format = "single-value")]
+pub struct AgentRevoked {
+    pub agent: Address,
+}
 
 #[contracttype]
 #[derive(Clone)]
 enum DataKey {
     Admin,
     Agent(Address),
+    /// Number of registered agents.
+    AgentCount,
+    /// Agent stored in slot `i`, for `i < AgentCount`.
+    AgentAt(u32),
+    /// Slot an agent occupies, so revocation can fill the gap.
+    AgentSlot(Address),
 }
 
 #[contract]
@@ -57,64 +105,162 @@ impl AgentRegistry {
         Ok(admin)
     }
 
+    fn validate_scopes(scopes: u32) -> Result<(), Error> {
+        if scopes & !ALL_SCOPES != 0 {
+            return Err(Error::InvalidScope);
+        }
+        Ok(())
+    }
+
+    /// Register a new agent with a `Scope` bitmask. Admin only.
+    pub fn register(env: Env, agent: Address, scopes: u32) -> Result<(), Error> {
+        let admin = Self::require_admin(&env)?;
+        Self::validate_scopes(scopes)?;
+
+        let key = DataKey::Agent(agent.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(Error::AlreadyRegistered);
+        }
+
+        let record = AgentRecord {
+            scopes,
+            registered_at: env.ledger().timestamp(),
+            expires_at: 0,
+            label: String::from_str(&env, ""),
+            registered_by: admin,
+        };
+        env.storage().persistent().set(&key, &record);
+
+        let count = Self::agent_count(env.clone());
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentAt(count), &agent);
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentSlot(agent.clone()), &count);
+        env.storage()
+            .persistent()
+            .set(&DataKey::AgentCount, &(count + 1));
+
+        AgentRegistered { agent }.publish(&env);
 
-    /// Register a new agent. Admin only.
-    pub fn register(env: Env, agent: Address) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Replace an agent's `Scope` bitmask. Admin only.
+    pub fn update_scopes(env: Env, agent: Address, scopes: u32) -> Result<(), Error> {
         Self::require_admin(&env)?;
+        Self::validate_scopes(scopes)?;
 
+        let mut record = Self::get_agent(env.clone(), agent.clone()).ok_or(Error::AgentNotFound)?;
+        record.scopes = scopes;
         env.storage()
             .persistent()
-            .set(&DataKey::Agent(agent.clone()), &true);
+            .set(&DataKey::Agent(agent.clone()), &record);
 
-        env.events()
-            .publish((symbol_short!("agent"), symbol_short!("reg")), agent);
+        AgentScopesUpdated { agent, scopes }.publish(&env);
 
         Ok(())
     }
 
-    pub fn update_scopes(
+    /// Set an agent's label and expiry. Admin only.
+    pub fn update_agent(
         env: Env,
         agent: Address,
-        scopes: u32,
+        label: String,
+        expires_at: u64,
     ) -> Result<(), Error> {
         Self::require_admin(&env)?;
 
-        if !env.storage().persistent().has(&DataKey::Agent(agent.clone())) {
-            return Err(Error::Unauthorized);
-        }
-
+        let mut record = Self::get_agent(env.clone(), agent.clone()).ok_or(Error::AgentNotFound)?;
+        record.label = label.clone();
+        record.expires_at = expires_at;
         env.storage()
             .persistent()
-            .set(&DataKey::Agent(agent.clone()), &scopes);
+            .set(&DataKey::Agent(agent.clone()), &record);
 
-        env.events().publish(
-            (symbol_short!("agent"), symbol_short!("scopes")),
-            (agent, scopes),
-        );
+        AgentUpdated {
+            agent,
+            label,
+            expires_at,
+        }
+        .publish(&env);
 
         Ok(())
     }
 
-
     /// Revoke an agent's authorization. Admin only.
     pub fn revoke_agent(env: Env, agent: Address) -> Result<(), Error> {
         Self::require_admin(&env)?;
 
-        env.storage()
+        let key = DataKey::Agent(agent.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::AgentNotFound);
+        }
+        env.storage().persistent().remove(&key);
+
+        // Move the last agent into the revoked agent's slot.
+        let slot_key = DataKey::AgentSlot(agent.clone());
+        let slot: u32 = env
+            .storage()
             .persistent()
-            .remove(&DataKey::Agent(agent.clone()));
+            .get(&slot_key)
+            .ok_or(Error::AgentNotFound)?;
+        let last = Self::agent_count(env.clone()) - 1;
+        if slot != last {
+            let moved: Address = env
+                .storage()
+                .persistent()
+                .get(&DataKey::AgentAt(last))
+                .ok_or(Error::AgentNotFound)?;
+            env.storage()
+                .persistent()
+                .set(&DataKey::AgentAt(slot), &moved);
+            env.storage()
+                .persistent()
+                .set(&DataKey::AgentSlot(moved), &slot);
+        }
+        env.storage().persistent().remove(&DataKey::AgentAt(last));
+        env.storage().persistent().remove(&slot_key);
+        env.storage().persistent().set(&DataKey::AgentCount, &last);
 
-        env.events().publish(
-            (symbol_short!("agent"), symbol_short!("revoke")),
-            agent,
-        );
+        AgentRevoked { agent }.publish(&env);
 
         Ok(())
     }
 
-    /// Check if an agent is authorized.
+    pub fn get_agent(env: Env, agent: Address) -> Option<AgentRecord> {
+        env.storage().persistent().get(&DataKey::Agent(agent))
+    }
+
+    /// Number of registered agents, expired ones included.
+    pub fn agent_count(env: Env) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::AgentCount)
+            .unwrap_or(0)
+    }
+
+    /// Up to `limit` (at most `MAX_PAGE_SIZE`) registered agents, expired
+    /// ones included, starting at slot `start`. Agents appear in
+    /// registration order until one is revoked; the last agent then takes
+    /// the revoked agent's slot.
+    pub fn list_agents(env: Env, start: u32, limit: u32) -> Vec<Address> {
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(Self::agent_count(env.clone()));
+        let mut agents = Vec::new(&env);
+        for i in start..end {
+            if let Some(agent) = env.storage().persistent().get(&DataKey::AgentAt(i)) {
+                agents.push_back(agent);
+            }
+        }
+        agents
+    }
+
+    /// Check if an agent is registered and not expired.
     pub fn is_authorized(env: Env, agent: Address) -> bool {
-        env.storage().persistent().has(&DataKey::Agent(agent))
+        Self::active_record(&env, agent).is_some()
     }
 
     /// Panic if an agent is not authorized.
@@ -124,18 +270,14 @@ impl AgentRegistry {
         }
     }
 
-       pub fn has_scope(env: Env, agent: Address, scope: Scope) -> bool {
-        match env
-            .storage()
-            .persistent()
-            .get::<_, u32>(&DataKey::Agent(agent))
-        {
-            Some(mask) => (mask & scope as u32) != 0,
+    pub fn has_scope(env: Env, agent: Address, scope: Scope) -> bool {
+        match Self::active_record(&env, agent) {
+            Some(record) => (record.scopes & scope as u32) != 0,
             None => false,
         }
     }
 
-      /// Enforce agent authorization + scope
+    /// Enforce agent authorization + scope
     pub fn require_scope(env: Env, agent: Address, scope: Scope) {
         agent.require_auth();
 
@@ -143,7 +285,11 @@ impl AgentRegistry {
             panic!("agent missing required scope");
         }
     }
-    
+
+    fn active_record(env: &Env, agent: Address) -> Option<AgentRecord> {
+        Self::get_agent(env.clone(), agent)
+            .filter(|r| r.expires_at == 0 || env.ledger().timestamp() < r.expires_at)
+    }
 }
 
 mod test;
@@ -1,77 +1,237 @@
 #![cfg(test)]
 
 use super::*;
-use soroban_sdk::testutils::Address as _;
-use soroban_sdk::Env;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::{vec, Env};
 
-#[test]
-fn test_registration_and_revocation() {
+struct Setup<'a> {
+    env: Env,
+    client: AgentRegistryClient<'a>,
+    admin: Address,
+}
+
+fn setup<'a>() -> Setup<'a> {
     let env = Env::default();
     env.mock_all_auths();
 
-    let contract_id = env.register_contract(None, AgentRegistry);
+    let contract_id = env.register(AgentRegistry, ());
     let client = AgentRegistryClient::new(&env, &contract_id);
 
     let admin = Address::generate(&env);
-    let agent = Address::generate(&env);
-
-    // Init
     client.init(&admin);
 
-    // Check not authorized initially
-    assert!(!client.is_authorized(&agent));
+    Setup { env, client, admin }
+}
+
+#[test]
+fn test_registration_and_revocation() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
 
-    // Register
-    client.register(&agent);
-    assert!(client.is_authorized(&agent));
+    assert!(!s.client.is_authorized(&agent));
 
-    // Revoke
-    client.revoke(&agent);
-    assert!(!client.is_authorized(&agent));
+    s.client.register(&agent, &(Scope::Renewals as u32));
+    assert!(s.client.is_authorized(&agent));
+    assert!(s.client.has_scope(&agent, &Scope::Renewals));
+
+    s.client.revoke_agent(&agent);
+    assert!(!s.client.is_authorized(&agent));
+    assert!(!s.client.has_scope(&agent, &Scope::Renewals));
+    assert_eq!(s.client.get_agent(&agent), None);
 }
 
 #[test]
-#[should_panic(expected = "agent not authorized")]
-fn test_require_authorized_panics() {
-    let env = Env::default();
-    let contract_id = env.register_contract(None, AgentRegistry);
-    let client = AgentRegistryClient::new(&env, &contract_id);
+fn test_register_stores_record() {
+    let s = setup();
+    s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    let agent = Address::generate(&s.env);
+    let scopes = Scope::Renewals as u32 | Scope::Approvals as u32;
+
+    s.client.register(&agent, &scopes);
+
+    assert_eq!(
+        s.client.get_agent(&agent),
+        Some(AgentRecord {
+            scopes,
+            registered_at: 1_000,
+            expires_at: 0,
+            label: String::from_str(&s.env, ""),
+            registered_by: s.admin.clone(),
+        })
+    );
+}
 
-    let agent = Address::generate(&env);
-    client.require_authorized(&agent);
+#[test]
+fn test_fresh_agent_scopes_are_readable() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    s.client.register(&agent, &(Scope::Approvals as u32));
+
+    assert!(s.client.has_scope(&agent, &Scope::Approvals));
+    assert!(!s.client.has_scope(&agent, &Scope::Renewals));
+    assert!(!s.client.has_scope(&agent, &Scope::GiftCards));
 }
 
 #[test]
-fn test_admin_auth() {
-    let env = Env::default();
-    env.mock_all_auths();
+fn test_update_scopes_keeps_record() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    s.client.register(&agent, &(Scope::Renewals as u32));
 
-    let contract_id = env.register_contract(None, AgentRegistry);
-    let client = AgentRegistryClient::new(&env, &contract_id);
+    s.client.update_scopes(&agent, &(Scope::GiftCards as u32));
 
-    let admin = Address::generate(&env);
-    let not_admin = Address::generate(&env);
-    let agent = Address::generate(&env);
+    assert!(s.client.has_scope(&agent, &Scope::GiftCards));
+    assert!(!s.client.has_scope(&agent, &Scope::Renewals));
+    assert_eq!(s.client.get_agent(&agent).unwrap().registered_by, s.admin);
+}
 
-    client.init(&admin);
+#[test]
+fn test_unknown_agent_not_found() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    assert_eq!(
+        s.client
+            .try_update_scopes(&agent, &(Scope::Renewals as u32)),
+        Err(Ok(Error::AgentNotFound))
+    );
+    assert_eq!(
+        s.client
+            .try_update_agent(&agent, &String::from_str(&s.env, ""), &0),
+        Err(Ok(Error::AgentNotFound))
+    );
+    assert_eq!(
+        s.client.try_revoke_agent(&agent),
+        Err(Ok(Error::AgentNotFound))
+    );
+
+    s.client.register(&agent, &(Scope::Renewals as u32));
+    s.client.revoke_agent(&agent);
+    assert_eq!(
+        s.client.try_revoke_agent(&agent),
+        Err(Ok(Error::AgentNotFound))
+    );
+}
 
-    // Try register with non-admin (mock_all_auths makes this pass, so we test auth requirements)
-    // In a real scenario without mock_all_auths it would fail auth.
-    // To properly test auth failure, we don't mock but we need to sign.
-    // Soroban's mock_all_auths is for convenience.
+#[test]
+fn test_invalid_scope_rejected() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_register(&agent, &(ALL_SCOPES + 1)),
+        Err(Ok(Error::InvalidScope))
+    );
+
+    s.client.register(&agent, &(Scope::Renewals as u32));
+    assert_eq!(
+        s.client.try_update_scopes(&agent, &8),
+        Err(Ok(Error::InvalidScope))
+    );
 }
 
 #[test]
-fn test_already_initialized() {
-    let env = Env::default();
-    env.mock_all_auths();
+fn test_register_twice_rejected() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    s.client.register(&agent, &(Scope::Renewals as u32));
+    assert_eq!(
+        s.client.try_register(&agent, &(Scope::Approvals as u32)),
+        Err(Ok(Error::AlreadyRegistered))
+    );
+}
 
-    let contract_id = env.register_contract(None, AgentRegistry);
-    let client = AgentRegistryClient::new(&env, &contract_id);
+#[test]
+fn test_expired_agent_loses_access() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    s.client.register(&agent, &(Scope::Renewals as u32));
+    s.client
+        .update_agent(&agent, &String::from_str(&s.env, "billing bot"), &500);
+
+    let record = s.client.get_agent(&agent).unwrap();
+    assert_eq!(record.label, String::from_str(&s.env, "billing bot"));
+    assert_eq!(record.expires_at, 500);
+    assert!(s.client.has_scope(&agent, &Scope::Renewals));
+
+    s.env.ledger().with_mut(|li| li.timestamp = 500);
+    assert!(!s.client.is_authorized(&agent));
+    assert!(!s.client.has_scope(&agent, &Scope::Renewals));
+}
 
-    let admin = Address::generate(&env);
-    client.init(&admin);
+#[test]
+fn test_list_agents() {
+    let s = setup();
+    let first = Address::generate(&s.env);
+    let second = Address::generate(&s.env);
+    let third = Address::generate(&s.env);
+    for agent in [&first, &second, &third] {
+        s.client.register(agent, &(Scope::Renewals as u32));
+    }
+    assert_eq!(s.client.agent_count(), 3);
+    assert_eq!(
+        s.client.list_agents(&0, &10),
+        vec![&s.env, first.clone(), second.clone(), third.clone()]
+    );
+    assert_eq!(s.client.list_agents(&1, &1), vec![&s.env, second.clone()]);
+    assert_eq!(s.client.list_agents(&3, &10), vec![&s.env]);
+
+    // The last agent fills the revoked agent's slot.
+    s.client.revoke_agent(&first);
+    assert_eq!(s.client.agent_count(), 2);
+    assert_eq!(
+        s.client.list_agents(&0, &10),
+        vec![&s.env, third.clone(), second.clone()]
+    );
+
+    s.client.revoke_agent(&second);
+    assert_eq!(s.client.list_agents(&0, &10), vec![&s.env, third]);
+}
 
-    let result = client.try_init(&admin);
-    assert_eq!(result, Err(Ok(Error::AlreadyInitialized)));
+#[test]
+fn test_list_agents_page_is_bounded() {
+    let s = setup();
+    for _ in 0..MAX_PAGE_SIZE + 1 {
+        s.client
+            .register(&Address::generate(&s.env), &(Scope::Renewals as u32));
+    }
+    assert_eq!(s.client.list_agents(&0, &u32::MAX).len(), MAX_PAGE_SIZE);
+    assert_eq!(s.client.list_agents(&MAX_PAGE_SIZE, &u32::MAX).len(), 1);
+}
+
+#[test]
+#[should_panic(expected = "agent not authorized")]
+fn test_require_authorized_panics() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    s.client.require_authorized(&agent);
+}
+
+#[test]
+#[should_panic(expected = "agent missing required scope")]
+fn test_require_scope_panics() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+    s.client.register(&agent, &(Scope::GiftCards as u32));
+    s.client.require_scope(&agent, &Scope::Renewals);
+}
+
+#[test]
+fn test_admin_auth() {
+    let s = setup();
+    let agent = Address::generate(&s.env);
+
+    s.env.set_auths(&[]);
+    assert!(s
+        .client
+        .try_register(&agent, &(Scope::Renewals as u32))
+        .is_err());
+    assert!(s.client.try_revoke_agent(&agent).is_err());
+}
+
+#[test]
+fn test_already_initialized() {
+    let s = setup();
+    assert_eq!(
+        s.client.try_init(&s.admin),
+        Err(Ok(Error::AlreadyInitialized))
+    );
 }
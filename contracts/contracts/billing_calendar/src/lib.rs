@@ -0,0 +1,128 @@
+#![no_std]
+
+//! Billing intervals shared by the subscription contracts, with the
+//! calendar arithmetic needed to advance a due date by one cycle.
+//!
+//! Timestamps are ledger timestamps (seconds since the Unix epoch, UTC).
+
+use soroban_sdk::contracttype;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Upper bound on a `Seconds` interval: ten 366-day years.
+pub const MAX_INTERVAL_SECONDS: u64 = 10 * 366 * SECONDS_PER_DAY;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BillingInterval {
+    /// A fixed number of seconds between cycles, at most
+    /// `MAX_INTERVAL_SECONDS`.
+    Seconds(u64),
+    /// Every calendar month on the given day (1-31), clamped to the last
+    /// day of shorter months.
+    Monthly(u32),
+    /// Every calendar year on the given month (1-12) and day; a Feb 29
+    /// anchor falls on Feb 28 in common years.
+    Yearly(u32, u32),
+}
+
+impl BillingInterval {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            BillingInterval::Seconds(seconds) => (1..=MAX_INTERVAL_SECONDS).contains(&seconds),
+            BillingInterval::Monthly(day) => (1..=31).contains(&day),
+            BillingInterval::Yearly(month, day) => {
+                // Validate against a leap year so Feb 29 is accepted.
+                (1..=12).contains(&month) && day >= 1 && day <= days_in_month(2000, month)
+            }
+        }
+    }
+
+    /// The due date following `due`. Calendar intervals return the first
+    /// anchor date strictly after `due`, keeping its time of day, so an
+    /// off-anchor `due` moves to the anchor in its own month or year when
+    /// that is still ahead.
+    pub fn next_due(&self, due: u64) -> u64 {
+        let time_of_day = due % SECONDS_PER_DAY;
+        let (year, month, day) = civil_from_days(due / SECONDS_PER_DAY);
+
+        let (year, month, day) = match *self {
+            BillingInterval::Seconds(seconds) => return due.saturating_add(seconds),
+            BillingInterval::Monthly(anchor_day) => {
+                let this_month = anchor_day.min(days_in_month(year, month));
+                if this_month > day {
+                    (year, month, this_month)
+                } else {
+                    let (year, month) = if month == 12 {
+                        (year + 1, 1)
+                    } else {
+                        (year, month + 1)
+                    };
+                    (year, month, anchor_day.min(days_in_month(year, month)))
+                }
+            }
+            BillingInterval::Yearly(anchor_month, anchor_day) => {
+                let this_year = anchor_day.min(days_in_month(year, anchor_month));
+                let year = if (anchor_month, this_year) > (month, day) {
+                    year
+                } else {
+                    year + 1
+                };
+                let day = anchor_day.min(days_in_month(year, anchor_month));
+                (year, anchor_month, day)
+            }
+        };
+
+        days_from_civil(year, month, day) * SECONDS_PER_DAY + time_of_day
+    }
+}
+
+pub fn is_leap_year(year: u64) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+pub fn days_in_month(year: u64, month: u32) -> u32 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => panic!("invalid month"),
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date on or after the
+/// epoch. Adapted from Howard Hinnant's `days_from_civil`.
+pub fn days_from_civil(year: u64, month: u32, day: u32) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month = month as u64;
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day as u64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Inverse of `days_from_civil`: `(year, month, day)` for days since
+/// 1970-01-01.
+pub fn civil_from_days(days: u64) -> (u64, u32, u32) {
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400;
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
+
+#[cfg(test)]
+mod test;
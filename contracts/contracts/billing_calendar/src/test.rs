@@ -0,0 +1,126 @@
+#![cfg(test)]
+
+use super::*;
+
+const DAY: u64 = 86_400;
+
+fn ts(year: u64, month: u32, day: u32) -> u64 {
+    days_from_civil(year, month, day) * DAY
+}
+
+#[test]
+fn test_civil_round_trip() {
+    assert_eq!(days_from_civil(1970, 1, 1), 0);
+    assert_eq!(civil_from_days(0), (1970, 1, 1));
+    assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+
+    for days in [0u64, 59, 365, 10_957, 11_016, 11_017, 19_782, 47_541] {
+        let (year, month, day) = civil_from_days(days);
+        assert_eq!(days_from_civil(year, month, day), days);
+    }
+}
+
+#[test]
+fn test_leap_years() {
+    assert!(is_leap_year(2024));
+    assert!(is_leap_year(2000));
+    assert!(!is_leap_year(1900));
+    assert!(!is_leap_year(2023));
+    assert_eq!(days_in_month(2024, 2), 29);
+    assert_eq!(days_in_month(2100, 2), 28);
+}
+
+#[test]
+fn test_seconds_interval() {
+    let interval = BillingInterval::Seconds(2_592_000);
+    assert_eq!(interval.next_due(1_000), 2_593_000);
+    assert_eq!(interval.next_due(u64::MAX - 1), u64::MAX);
+}
+
+#[test]
+fn test_monthly_keeps_anchor_day() {
+    let interval = BillingInterval::Monthly(15);
+    let mut due = ts(2024, 1, 15) + 3_600;
+    for month in 2..=12 {
+        due = interval.next_due(due);
+        assert_eq!(due, ts(2024, month, 15) + 3_600);
+    }
+    assert_eq!(interval.next_due(due), ts(2025, 1, 15) + 3_600);
+}
+
+#[test]
+fn test_monthly_31st_anchor_clamps_and_recovers() {
+    let interval = BillingInterval::Monthly(31);
+    let jan = ts(2023, 1, 31);
+    let feb = interval.next_due(jan);
+    assert_eq!(feb, ts(2023, 2, 28));
+    let mar = interval.next_due(feb);
+    assert_eq!(mar, ts(2023, 3, 31));
+    let apr = interval.next_due(mar);
+    assert_eq!(apr, ts(2023, 4, 30));
+    assert_eq!(interval.next_due(apr), ts(2023, 5, 31));
+}
+
+#[test]
+fn test_monthly_29th_anchor_in_leap_year() {
+    let interval = BillingInterval::Monthly(29);
+    assert_eq!(interval.next_due(ts(2024, 1, 29)), ts(2024, 2, 29));
+    assert_eq!(interval.next_due(ts(2023, 1, 29)), ts(2023, 2, 28));
+}
+
+#[test]
+fn test_yearly_feb_29_anchor() {
+    let interval = BillingInterval::Yearly(2, 29);
+    let due = ts(2024, 2, 29);
+    let next = interval.next_due(due);
+    assert_eq!(next, ts(2025, 2, 28));
+    let next = interval.next_due(interval.next_due(interval.next_due(next)));
+    assert_eq!(next, ts(2028, 2, 29));
+}
+
+#[test]
+fn test_yearly_regular_anchor() {
+    let interval = BillingInterval::Yearly(12, 31);
+    assert_eq!(interval.next_due(ts(2024, 12, 31)), ts(2025, 12, 31));
+}
+
+#[test]
+fn test_monthly_off_anchor_due_moves_to_this_months_anchor() {
+    let interval = BillingInterval::Monthly(31);
+    assert_eq!(interval.next_due(ts(2025, 1, 5) + 60), ts(2025, 1, 31) + 60);
+    assert_eq!(interval.next_due(ts(2025, 2, 10)), ts(2025, 2, 28));
+    // Past this month's anchor: next month's.
+    let interval = BillingInterval::Monthly(15);
+    assert_eq!(interval.next_due(ts(2025, 1, 20)), ts(2025, 2, 15));
+    assert_eq!(interval.next_due(ts(2025, 12, 20)), ts(2026, 1, 15));
+}
+
+#[test]
+fn test_yearly_off_anchor_due_moves_to_this_years_anchor() {
+    let interval = BillingInterval::Yearly(12, 31);
+    assert_eq!(
+        interval.next_due(ts(2024, 3, 1) + 60),
+        ts(2024, 12, 31) + 60
+    );
+    assert_eq!(interval.next_due(ts(2024, 12, 30)), ts(2024, 12, 31));
+    // Past this year's anchor: next year's.
+    let interval = BillingInterval::Yearly(2, 29);
+    assert_eq!(interval.next_due(ts(2025, 1, 10)), ts(2025, 2, 28));
+    assert_eq!(interval.next_due(ts(2025, 3, 1)), ts(2026, 2, 28));
+    assert_eq!(interval.next_due(ts(2027, 6, 1)), ts(2028, 2, 29));
+}
+
+#[test]
+fn test_validity() {
+    assert!(BillingInterval::Seconds(1).is_valid());
+    assert!(!BillingInterval::Seconds(0).is_valid());
+    assert!(BillingInterval::Seconds(MAX_INTERVAL_SECONDS).is_valid());
+    assert!(!BillingInterval::Seconds(MAX_INTERVAL_SECONDS + 1).is_valid());
+    assert!(!BillingInterval::Seconds(u64::MAX).is_valid());
+    assert!(BillingInterval::Monthly(31).is_valid());
+    assert!(!BillingInterval::Monthly(0).is_valid());
+    assert!(!BillingInterval::Monthly(32).is_valid());
+    assert!(BillingInterval::Yearly(2, 29).is_valid());
+    assert!(!BillingInterval::Yearly(2, 30).is_valid());
+    assert!(!BillingInterval::Yearly(13, 1).is_valid());
+}
@@ -3,11 +3,13 @@ use soroban_sdk::{
     String, Vec,
 };
 
+pub use billing_calendar::BillingInterval;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SubscriptionMetadata {
     pub service_id: String,
-    pub billing_interval: u64,
+    pub billing_interval: BillingInterval,
     pub expected_amount: i128,
     pub next_renewal: u64,
     pub is_active: bool,
@@ -27,7 +29,7 @@ pub struct SubscriptionCreatedEvent {
     pub subscription_id: BytesN<32>,
     pub user: Address,
     pub service_id: String,
-    pub billing_interval: u64,
+    pub billing_interval: BillingInterval,
     pub expected_amount: i128,
     pub next_renewal: u64,
 }
@@ -38,7 +40,7 @@ pub struct SubscriptionUpdatedEvent {
     pub subscription_id: BytesN<32>,
     pub user: Address,
     pub service_id: String,
-    pub billing_interval: u64,
+    pub billing_interval: BillingInterval,
     pub expected_amount: i128,
     pub next_renewal: u64,
 }
@@ -61,13 +63,13 @@ impl SubscriptionRegistry {
         env: Env,
         user: Address,
         service_id: String,
-        billing_interval: u64,
+        billing_interval: BillingInterval,
         expected_amount: i128,
         next_renewal: u64,
     ) -> BytesN<32> {
         user.require_auth();
-        if billing_interval == 0 {
-            panic!("billing_interval must be greater than 0");
+        if !billing_interval.is_valid() {
+            panic!("invalid billing_interval");
         }
         if expected_amount <= 0 {
             panic!("expected_amount must be non-negative");
@@ -112,7 +114,7 @@ impl SubscriptionRegistry {
 
         let metadata = SubscriptionMetadata {
             service_id: service_id.clone(),
-            billing_interval,
+            billing_interval: billing_interval.clone(),
             expected_amount,
             next_renewal,
             is_active: true,
@@ -145,7 +147,7 @@ impl SubscriptionRegistry {
         subscription_id: BytesN<32>,
         user: Address,
         service_id: Option<String>,
-        billing_interval: Option<u64>,
+        billing_interval: Option<BillingInterval>,
         expected_amount: Option<i128>,
         next_renewal: Option<u64>,
     ) {
@@ -164,8 +166,8 @@ impl SubscriptionRegistry {
             metadata.service_id = sid;
         }
         if let Some(bi) = billing_interval {
-            if bi == 0 {
-                panic!("billing_interval must be greater than 0");
+            if !bi.is_valid() {
+                panic!("invalid billing_interval");
             }
             metadata.billing_interval = bi;
         }
@@ -190,7 +192,7 @@ impl SubscriptionRegistry {
             subscription_id: subscription_id.clone(),
             user: user.clone(),
             service_id: metadata.service_id.clone(),
-            billing_interval: metadata.billing_interval,
+            billing_interval: metadata.billing_interval.clone(),
             expected_amount: metadata.expected_amount,
             next_renewal: metadata.next_renewal,
         }
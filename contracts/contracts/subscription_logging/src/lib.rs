@@ -1,9 +1,29 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contractevent, contractimpl, contracttype, vec, Address, Env, String, Vec,
+    contract, contracterror, contractevent, contractimpl, contracttype, vec, xdr::ToXdr, Address,
+    BytesN, Env, String, Vec,
 };
 
+pub use billing_calendar::BillingInterval;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    UnauthorizedWriter = 3,
+    WriterNotFound = 4,
+    InvalidRetentionPolicy = 5,
+    /// An index or log entry that should exist was not found, e.g. because
+    /// it was archived; restore it or call `extend_logs` earlier.
+    LogEntryMissing = 6,
+}
+
+/// Upper bound on entries returned by a single `get_logs_page` call.
+pub const MAX_PAGE_SIZE: u32 = 50;
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum LogEvent {
@@ -13,6 +33,89 @@ pub enum LogEvent {
     Failure,
     Retry,
     Cancellation,
+    Creation,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReminderPayload {
+    pub due_at: u64,
+    pub days_before: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApprovalPayload {
+    pub approval_id: u64,
+    pub max_spend: i128,
+    pub expires_at: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenewalPayload {
+    pub amount: i128,
+    pub cycle_id: u64,
+    /// Ledger sequence the renewal settled in.
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FailurePayload {
+    /// Writer-defined reason code.
+    pub reason: u32,
+    pub failure_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPayload {
+    pub failure_count: u32,
+    pub retry_after_ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancellationPayload {
+    pub canceled_by: Address,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreationPayload {
+    pub owner: Address,
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub frequency: BillingInterval,
+}
+
+/// Typed body of a log entry; the variant determines the entry's `LogEvent`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LogPayload {
+    Reminder(ReminderPayload),
+    Approval(ApprovalPayload),
+    Renewal(RenewalPayload),
+    Failure(FailurePayload),
+    Retry(RetryPayload),
+    Cancellation(CancellationPayload),
+    Creation(CreationPayload),
+}
+
+impl LogPayload {
+    pub fn event(&self) -> LogEvent {
+        match self {
+            LogPayload::Reminder(_) => LogEvent::Reminder,
+            LogPayload::Approval(_) => LogEvent::Approval,
+            LogPayload::Renewal(_) => LogEvent::Renewal,
+            LogPayload::Failure(_) => LogEvent::Failure,
+            LogPayload::Retry(_) => LogEvent::Retry,
+            LogPayload::Cancellation(_) => LogEvent::Cancellation,
+            LogPayload::Creation(_) => LogEvent::Creation,
+        }
+    }
 }
 
 #[contracttype]
@@ -21,20 +124,82 @@ pub struct LogEntry {
     pub sub_id: u64,
     pub event: LogEvent,
     pub timestamp: u64,
-    pub data: String,
+    pub payload: LogPayload,
+    /// Free-form annotation; structured data belongs in `payload`.
+    pub note: String,
+    pub writer: Address,
+    /// SHA-256 of the XDR of the previous entry for this subscription, or
+    /// all zeroes for the first entry.
+    pub prev_hash: BytesN<32>,
+}
+
+/// TTL extension applied to log entries, in ledgers. Entries whose TTL
+/// drops below `ttl_threshold` are extended to `ttl_extend_to`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetentionPolicy {
+    pub ttl_threshold: u32,
+    pub ttl_extend_to: u32,
+}
+
+/// Selects entries whose event is in `events` (any event if empty) and
+/// whose timestamp lies in `from_timestamp..=to_timestamp`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogFilter {
+    pub events: Vec<LogEvent>,
+    pub from_timestamp: u64,
+    pub to_timestamp: u64,
 }
 
 #[contracttype]
 #[derive(Clone)]
 enum DataKey {
     Admin,
-    Logs(u64),
+    Writer(Address),
+    Retention,
+    LogCount(u64),
+    LogStart(u64),
+    LogHead(u64),
+    Log(u64, u32),
+    // Per-event secondary index: position within the kind -> log index.
+    KindCount(u64, LogEvent),
+    KindStart(u64, LogEvent),
+    KindLog(u64, LogEvent, u32),
 }
 
 #[contractevent]
 pub struct LogAppended {
     pub sub_id: u64,
     pub event: LogEvent,
+    pub payload: LogPayload,
+    pub writer: Address,
+}
+
+#[contractevent]
+pub struct WriterAdded {
+    pub writer: Address,
+}
+
+#[contractevent]
+pub struct WriterRemoved {
+    pub writer: Address,
+}
+
+#[contractevent]
+pub struct RetentionUpdated {
+    pub ttl_threshold: u32,
+    pub ttl_extend_to: u32,
+}
+
+/// Entries `from..to` of `sub_id` were deleted. `digest` is the SHA-256 of
+/// the XDR-encoded `Vec<LogEntry>` that was removed.
+#[contractevent]
+pub struct LogsPruned {
+    pub sub_id: u64,
+    pub from: u32,
+    pub to: u32,
+    pub digest: BytesN<32>,
 }
 
 #[contract]
@@ -42,52 +207,509 @@ pub struct SubscriptionLoggingContract;
 
 #[contractimpl]
 impl SubscriptionLoggingContract {
-    pub fn init(env: Env, admin: Address) {
+    pub fn init(env: Env, admin: Address) -> Result<(), Error> {
         if env.storage().instance().has(&DataKey::Admin) {
-            panic!("Already initialized");
+            return Err(Error::AlreadyInitialized);
         }
         env.storage().instance().set(&DataKey::Admin, &admin);
+        Ok(())
     }
 
-    fn require_admin(env: &Env) {
-        let admin: Address = env.storage().instance().get(&DataKey::Admin).unwrap();
-        admin.require_auth();
+    fn get_admin(env: &Env) -> Result<Address, Error> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(Error::NotInitialized)
     }
 
-    pub fn record_log(env: Env, sub_id: u64, event: LogEvent, data: String) {
-        Self::require_admin(&env);
+    fn require_admin(env: &Env) -> Result<Address, Error> {
+        let admin = Self::get_admin(env)?;
+        admin.require_auth();
+        Ok(admin)
+    }
 
-        let key = DataKey::Logs(sub_id);
+    /// Register an address (account or contract) allowed to append logs. Admin only.
+    pub fn add_writer(env: Env, writer: Address) -> Result<(), Error> {
+        Self::require_admin(&env)?;
 
-        let mut logs: Vec<LogEntry> = env
-            .storage()
+        env.storage()
             .persistent()
-            .get(&key)
-            .unwrap_or(vec![&env]);
+            .set(&DataKey::Writer(writer.clone()), &true);
+
+        WriterAdded { writer }.publish(&env);
+        Ok(())
+    }
+
+    /// Revoke a writer registration. Admin only.
+    pub fn remove_writer(env: Env, writer: Address) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+
+        let key = DataKey::Writer(writer.clone());
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::WriterNotFound);
+        }
+        env.storage().persistent().remove(&key);
+
+        WriterRemoved { writer }.publish(&env);
+        Ok(())
+    }
+
+    /// Check whether `writer` may append logs. The admin is always a writer.
+    pub fn is_writer(env: Env, writer: Address) -> bool {
+        match Self::get_admin(&env) {
+            Ok(admin) if admin == writer => true,
+            _ => env.storage().persistent().has(&DataKey::Writer(writer)),
+        }
+    }
+
+    /// Set the TTL policy applied to log entries. Admin only.
+    pub fn set_retention(env: Env, policy: RetentionPolicy) -> Result<(), Error> {
+        Self::require_admin(&env)?;
+
+        if policy.ttl_extend_to == 0 || policy.ttl_threshold > policy.ttl_extend_to {
+            return Err(Error::InvalidRetentionPolicy);
+        }
+        env.storage().instance().set(&DataKey::Retention, &policy);
+
+        RetentionUpdated {
+            ttl_threshold: policy.ttl_threshold,
+            ttl_extend_to: policy.ttl_extend_to,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_retention(env: Env) -> Option<RetentionPolicy> {
+        env.storage().instance().get(&DataKey::Retention)
+    }
+
+    /// Append a log entry on behalf of `writer`, which must be registered.
+    pub fn record_log(
+        env: Env,
+        writer: Address,
+        sub_id: u64,
+        payload: LogPayload,
+        note: String,
+    ) -> Result<(), Error> {
+        if !Self::is_writer(env.clone(), writer.clone()) {
+            return Err(Error::UnauthorizedWriter);
+        }
+        writer.require_auth();
+
+        let count = Self::get_log_count(env.clone(), sub_id);
+        let event = payload.event();
 
         let entry = LogEntry {
             sub_id,
             event: event.clone(),
             timestamp: env.ledger().timestamp(),
-            data,
+            payload: payload.clone(),
+            note,
+            writer: writer.clone(),
+            prev_hash: Self::get_log_head(env.clone(), sub_id),
         };
 
-        logs.push_back(entry);
+        env.storage()
+            .persistent()
+            .set(&DataKey::LogHead(sub_id), &Self::hash_entry(&env, &entry));
+        env.storage()
+            .persistent()
+            .set(&DataKey::Log(sub_id, count), &entry);
+        env.storage()
+            .persistent()
+            .set(&DataKey::LogCount(sub_id), &(count + 1));
+
+        let kind_count = Self::kind_count(&env, sub_id, &event);
+        env.storage()
+            .persistent()
+            .set(&DataKey::KindLog(sub_id, event.clone(), kind_count), &count);
+        env.storage().persistent().set(
+            &DataKey::KindCount(sub_id, event.clone()),
+            &(kind_count + 1),
+        );
 
-        env.storage().persistent().set(&key, &logs);
+        if let Some(policy) = Self::get_retention(env.clone()) {
+            Self::extend_entry(&env, &policy, &DataKey::Log(sub_id, count));
+            Self::extend_entry(&env, &policy, &DataKey::LogCount(sub_id));
+            Self::extend_entry(&env, &policy, &DataKey::LogStart(sub_id));
+            Self::extend_entry(&env, &policy, &DataKey::LogHead(sub_id));
+            Self::extend_entry(
+                &env,
+                &policy,
+                &DataKey::KindLog(sub_id, event.clone(), kind_count),
+            );
+            Self::extend_entry(&env, &policy, &DataKey::KindCount(sub_id, event.clone()));
+            Self::extend_entry(&env, &policy, &DataKey::KindStart(sub_id, event.clone()));
+        }
 
-        LogAppended { sub_id, event }.publish(&env);
+        LogAppended {
+            sub_id,
+            event,
+            payload,
+            writer,
+        }
+        .publish(&env);
+        Ok(())
     }
 
+    /// Re-apply the retention policy to entries `cursor..cursor + limit` so
+    /// recent history is not archived. Callable by anyone; `limit` is clamped
+    /// to `MAX_PAGE_SIZE`.
+    pub fn extend_logs(env: Env, sub_id: u64, cursor: u32, limit: u32) {
+        let Some(policy) = Self::get_retention(env.clone()) else {
+            return;
+        };
+
+        let start = cursor.max(Self::get_log_start(env.clone(), sub_id));
+        let end = start
+            .saturating_add(limit.min(MAX_PAGE_SIZE))
+            .min(Self::get_log_count(env.clone(), sub_id));
+        for index in start..end {
+            Self::extend_entry(&env, &policy, &DataKey::Log(sub_id, index));
+        }
+        Self::extend_entry(&env, &policy, &DataKey::LogCount(sub_id));
+        Self::extend_entry(&env, &policy, &DataKey::LogStart(sub_id));
+        Self::extend_entry(&env, &policy, &DataKey::LogHead(sub_id));
+
+        // Keep the per-event index alive alongside the entries it points to.
+        for event in Self::all_events(&env).iter() {
+            Self::extend_entry(&env, &policy, &DataKey::KindCount(sub_id, event.clone()));
+            Self::extend_entry(&env, &policy, &DataKey::KindStart(sub_id, event.clone()));
+
+            let mut pos = Self::kind_position(&env, sub_id, &event, start);
+            while let Some(index) = env.storage().persistent().get::<_, u32>(&DataKey::KindLog(
+                sub_id,
+                event.clone(),
+                pos,
+            )) {
+                if index >= end {
+                    break;
+                }
+                Self::extend_entry(&env, &policy, &DataKey::KindLog(sub_id, event.clone(), pos));
+                pos += 1;
+            }
+        }
+    }
+
+    /// Delete the oldest entries of `sub_id` recorded before
+    /// `before_timestamp`, at most `MAX_PAGE_SIZE` per call. Emits
+    /// `LogsPruned` with a digest of what was removed and returns the number
+    /// of entries deleted. Admin only.
+    pub fn prune_logs(env: Env, sub_id: u64, before_timestamp: u64) -> Result<u32, Error> {
+        Self::require_admin(&env)?;
+
+        let start = Self::get_log_start(env.clone(), sub_id);
+        let count = Self::get_log_count(env.clone(), sub_id);
+        let limit = start.saturating_add(MAX_PAGE_SIZE).min(count);
+
+        let mut removed: Vec<LogEntry> = vec![&env];
+        let mut next = start;
+        while next < limit {
+            let key = DataKey::Log(sub_id, next);
+            if let Some(entry) = env.storage().persistent().get::<_, LogEntry>(&key) {
+                if entry.timestamp >= before_timestamp {
+                    break;
+                }
+                env.storage().persistent().remove(&key);
+
+                // Pruning is oldest-first, so this entry heads its kind index.
+                let kind_start = Self::kind_start(&env, sub_id, &entry.event);
+                env.storage().persistent().remove(&DataKey::KindLog(
+                    sub_id,
+                    entry.event.clone(),
+                    kind_start,
+                ));
+                env.storage().persistent().set(
+                    &DataKey::KindStart(sub_id, entry.event.clone()),
+                    &(kind_start + 1),
+                );
+
+                removed.push_back(entry);
+            }
+            next += 1;
+        }
+
+        if next == start {
+            return Ok(0);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::LogStart(sub_id), &next);
+
+        let pruned = removed.len();
+        LogsPruned {
+            sub_id,
+            from: start,
+            to: next,
+            digest: env.crypto().sha256(&removed.to_xdr(&env)).into(),
+        }
+        .publish(&env);
+        Ok(pruned)
+    }
+
+    /// Hash of the newest entry for `sub_id`, i.e. the head of its hash
+    /// chain. All zeroes if nothing has been logged.
+    pub fn get_log_head(env: Env, sub_id: u64) -> BytesN<32> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LogHead(sub_id))
+            .unwrap_or(BytesN::from_array(&env, &[0; 32]))
+    }
+
+    /// Check that entries `from..to` of `sub_id` form an unbroken hash chain
+    /// and that the last one is what the next link expects: entry `to`'s
+    /// `prev_hash`, or the head when `to` reaches the newest entry. Returns
+    /// `false` for empty ranges and ranges outside the retained entries.
+    pub fn verify_logs(env: Env, sub_id: u64, from: u32, to: u32) -> bool {
+        let start = Self::get_log_start(env.clone(), sub_id);
+        let count = Self::get_log_count(env.clone(), sub_id);
+        if from < start || to > count || from >= to {
+            return false;
+        }
+
+        let mut expected: Option<BytesN<32>> = None;
+        for index in from..to {
+            let Some(entry) = env
+                .storage()
+                .persistent()
+                .get::<_, LogEntry>(&DataKey::Log(sub_id, index))
+            else {
+                return false;
+            };
+            if let Some(hash) = expected {
+                if entry.prev_hash != hash {
+                    return false;
+                }
+            }
+            expected = Some(Self::hash_entry(&env, &entry));
+        }
+
+        if to == count {
+            return expected == Some(Self::get_log_head(env, sub_id));
+        }
+        match env
+            .storage()
+            .persistent()
+            .get::<_, LogEntry>(&DataKey::Log(sub_id, to))
+        {
+            Some(next) => expected == Some(next.prev_hash),
+            None => false,
+        }
+    }
+
+    /// Index of the oldest entry still stored for `sub_id`.
+    pub fn get_log_start(env: Env, sub_id: u64) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LogStart(sub_id))
+            .unwrap_or(0)
+    }
+
+    /// Number of entries ever appended for `sub_id`.
+    pub fn get_log_count(env: Env, sub_id: u64) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::LogCount(sub_id))
+            .unwrap_or(0)
+    }
+
+    /// Entries `cursor..cursor + limit` for `sub_id`, oldest first. `limit`
+    /// is clamped to `MAX_PAGE_SIZE`. Pruned entries are skipped, so page
+    /// from `get_log_start` and advance the cursor by `limit`.
+    pub fn get_logs_page(env: Env, sub_id: u64, cursor: u32, limit: u32) -> Vec<LogEntry> {
+        let count = Self::get_log_count(env.clone(), sub_id);
+        let end = cursor.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+        Self::read_range(&env, sub_id, cursor, end)
+    }
+
+    /// Full retained history for `sub_id`. Reads every entry, so only
+    /// suitable for short histories; use `get_logs_page` otherwise.
     pub fn get_logs(env: Env, sub_id: u64) -> Vec<LogEntry> {
-        let key = DataKey::Logs(sub_id);
+        let start = Self::get_log_start(env.clone(), sub_id);
+        let count = Self::get_log_count(env.clone(), sub_id);
+        Self::read_range(&env, sub_id, start, count)
+    }
+
+    /// Entries matching `filter`, oldest first, at most `limit` (clamped to
+    /// `MAX_PAGE_SIZE`). Uses the per-event index, so only matching kinds
+    /// are read.
+    pub fn query_logs(
+        env: Env,
+        sub_id: u64,
+        filter: LogFilter,
+        limit: u32,
+    ) -> Result<Vec<LogEntry>, Error> {
+        let events = if filter.events.is_empty() {
+            Self::all_events(&env)
+        } else {
+            filter.events.clone()
+        };
+
+        // One cursor per requested kind, starting at the first entry at or
+        // after `from_timestamp`; merged by log index to keep global order.
+        let mut cursors: Vec<u32> = vec![&env];
+        let mut ends: Vec<u32> = vec![&env];
+        for event in events.iter() {
+            cursors.push_back(Self::kind_lower_bound(
+                &env,
+                sub_id,
+                &event,
+                filter.from_timestamp,
+            )?);
+            ends.push_back(Self::kind_count(&env, sub_id, &event));
+        }
+
+        let limit = limit.min(MAX_PAGE_SIZE);
+        let mut logs = vec![&env];
+        while logs.len() < limit {
+            let mut next: Option<(u32, u32)> = None;
+            for (i, event) in events.iter().enumerate() {
+                let i = i as u32;
+                let pos = cursors.get_unchecked(i);
+                if pos >= ends.get_unchecked(i) {
+                    continue;
+                }
+                let index: u32 = env
+                    .storage()
+                    .persistent()
+                    .get(&DataKey::KindLog(sub_id, event, pos))
+                    .ok_or(Error::LogEntryMissing)?;
+                if next.is_none_or(|(_, best)| index < best) {
+                    next = Some((i, index));
+                }
+            }
+
+            let Some((i, index)) = next else {
+                break;
+            };
+            let entry: LogEntry = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Log(sub_id, index))
+                .ok_or(Error::LogEntryMissing)?;
+            if entry.timestamp > filter.to_timestamp {
+                break;
+            }
+            logs.push_back(entry);
+            cursors.set(i, cursors.get_unchecked(i) + 1);
+        }
+        Ok(logs)
+    }
+
+    /// Most recent retained entry of kind `event`, if any.
+    pub fn get_latest_log(env: Env, sub_id: u64, event: LogEvent) -> Option<LogEntry> {
+        let count = Self::kind_count(&env, sub_id, &event);
+        if count <= Self::kind_start(&env, sub_id, &event) {
+            return None;
+        }
+        let index: u32 =
+            env.storage()
+                .persistent()
+                .get(&DataKey::KindLog(sub_id, event, count - 1))?;
+        env.storage().persistent().get(&DataKey::Log(sub_id, index))
+    }
+
+    fn kind_count(env: &Env, sub_id: u64, event: &LogEvent) -> u32 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::KindCount(sub_id, event.clone()))
+            .unwrap_or(0)
+    }
 
+    fn kind_start(env: &Env, sub_id: u64, event: &LogEvent) -> u32 {
         env.storage()
             .persistent()
-            .get(&key)
-            .unwrap_or(vec![&env])
+            .get(&DataKey::KindStart(sub_id, event.clone()))
+            .unwrap_or(0)
+    }
+
+    /// First position in the `event` index whose entry was recorded at or
+    /// after `timestamp`. Timestamps are non-decreasing, so binary search.
+    fn kind_lower_bound(
+        env: &Env,
+        sub_id: u64,
+        event: &LogEvent,
+        timestamp: u64,
+    ) -> Result<u32, Error> {
+        let mut lo = Self::kind_start(env, sub_id, event);
+        let mut hi = Self::kind_count(env, sub_id, event);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let index: u32 = env
+                .storage()
+                .persistent()
+                .get(&DataKey::KindLog(sub_id, event.clone(), mid))
+                .ok_or(Error::LogEntryMissing)?;
+            let entry: LogEntry = env
+                .storage()
+                .persistent()
+                .get(&DataKey::Log(sub_id, index))
+                .ok_or(Error::LogEntryMissing)?;
+            if entry.timestamp < timestamp {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        Ok(lo)
+    }
+
+    /// First position in the `event` index pointing at log index `index` or
+    /// later. Missing index entries end the search early.
+    fn kind_position(env: &Env, sub_id: u64, event: &LogEvent, index: u32) -> u32 {
+        let mut lo = Self::kind_start(env, sub_id, event);
+        let mut hi = Self::kind_count(env, sub_id, event);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match env.storage().persistent().get::<_, u32>(&DataKey::KindLog(
+                sub_id,
+                event.clone(),
+                mid,
+            )) {
+                Some(found) if found < index => lo = mid + 1,
+                Some(_) => hi = mid,
+                None => return hi,
+            }
+        }
+        lo
+    }
+
+    fn all_events(env: &Env) -> Vec<LogEvent> {
+        vec![
+            env,
+            LogEvent::Reminder,
+            LogEvent::Approval,
+            LogEvent::Renewal,
+            LogEvent::Failure,
+            LogEvent::Retry,
+            LogEvent::Cancellation,
+            LogEvent::Creation,
+        ]
+    }
+
+    fn hash_entry(env: &Env, entry: &LogEntry) -> BytesN<32> {
+        env.crypto().sha256(&entry.clone().to_xdr(env)).into()
+    }
+
+    fn extend_entry(env: &Env, policy: &RetentionPolicy, key: &DataKey) {
+        if env.storage().persistent().has(key) {
+            env.storage()
+                .persistent()
+                .extend_ttl(key, policy.ttl_threshold, policy.ttl_extend_to);
+        }
+    }
+
+    fn read_range(env: &Env, sub_id: u64, start: u32, end: u32) -> Vec<LogEntry> {
+        let mut logs = vec![env];
+        for index in start..end {
+            if let Some(entry) = env.storage().persistent().get(&DataKey::Log(sub_id, index)) {
+                logs.push_back(entry);
+            }
+        }
+        logs
     }
 }
 
 #[cfg(test)]
-mod test;
\ No newline at end of file
+mod test;
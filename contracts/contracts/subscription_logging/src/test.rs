@@ -1,62 +1,511 @@
-use soroban_sdk::{contract, contractimpl, contracttype, Env, String, Vec};
+#![cfg(test)]
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum LogEvent {
-    Reminder,
-    Approval,
-    Renewal,
-    Failure,
-    Retry,
-    Cancellation,
+use super::*;
+use soroban_sdk::testutils::{storage::Persistent as _, Address as _, Ledger};
+use soroban_sdk::xdr::ToXdr;
+use soroban_sdk::Env;
+
+/// A representative payload for each kind of event.
+fn payload(env: &Env, event: LogEvent) -> LogPayload {
+    match event {
+        LogEvent::Reminder => LogPayload::Reminder(ReminderPayload {
+            due_at: 1_000,
+            days_before: 3,
+        }),
+        LogEvent::Approval => LogPayload::Approval(ApprovalPayload {
+            approval_id: 1,
+            max_spend: 100,
+            expires_at: 1_000,
+        }),
+        LogEvent::Renewal => LogPayload::Renewal(RenewalPayload {
+            amount: 100,
+            cycle_id: 1,
+            ledger: 1,
+        }),
+        LogEvent::Failure => LogPayload::Failure(FailurePayload {
+            reason: 1,
+            failure_count: 4,
+        }),
+        LogEvent::Retry => LogPayload::Retry(RetryPayload {
+            failure_count: 1,
+            retry_after_ledger: 10,
+        }),
+        LogEvent::Cancellation => LogPayload::Cancellation(CancellationPayload {
+            canceled_by: Address::generate(env),
+        }),
+        LogEvent::Creation => LogPayload::Creation(CreationPayload {
+            owner: Address::generate(env),
+            merchant: Address::generate(env),
+            token: Address::generate(env),
+            amount: 100,
+            frequency: BillingInterval::Seconds(2_592_000),
+        }),
+    }
 }
 
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct LogEntry {
-    pub sub_id: u64,
-    pub event: LogEvent,
-    pub timestamp: u64,
-    pub data: String,
+fn setup<'a>() -> (Env, SubscriptionLoggingContractClient<'a>, Address) {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(SubscriptionLoggingContract, ());
+    let client = SubscriptionLoggingContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    client.init(&admin);
+
+    (env, client, admin)
 }
 
-#[contracttype]
-#[derive(Clone)]
-enum DataKey {
-    Logs(u64),
+#[test]
+fn test_append_log_entry() {
+    let (env, client, admin) = setup();
+    env.ledger().with_mut(|li| li.timestamp = 1_000);
+
+    let note = String::from_str(&env, "Renewal successful");
+    let renewal = payload(&env, LogEvent::Renewal);
+    client.record_log(&admin, &1, &renewal, &note);
+
+    let logs = client.get_logs(&1);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(
+        logs.get(0).unwrap(),
+        LogEntry {
+            sub_id: 1,
+            event: LogEvent::Renewal,
+            timestamp: 1_000,
+            payload: renewal,
+            note,
+            writer: admin,
+            prev_hash: BytesN::from_array(&env, &[0; 32]),
+        }
+    );
 }
 
-#[contract]
-pub struct SubscriptionLoggingContract;
+#[test]
+fn test_get_logs_for_subscription() {
+    let (env, client, admin) = setup();
 
-#[contractimpl]
-impl SubscriptionLoggingContract {
-    pub fn record_log(env: Env, sub_id: u64, event: LogEvent, data: String) {
-        let key = DataKey::Logs(sub_id);
+    client.record_log(
+        &admin,
+        &1,
+        &payload(&env, LogEvent::Renewal),
+        &String::from_str(&env, "a"),
+    );
+    client.record_log(
+        &admin,
+        &2,
+        &payload(&env, LogEvent::Failure),
+        &String::from_str(&env, "b"),
+    );
+    client.record_log(
+        &admin,
+        &1,
+        &payload(&env, LogEvent::Cancellation),
+        &String::from_str(&env, "c"),
+    );
 
-        let mut logs: Vec<LogEntry> = env
-            .storage()
-            .persistent()
-            .get(&key)
-            .unwrap_or(Vec::new(&env));
-
-        let entry = LogEntry {
-            sub_id,
-            event,
-            timestamp: env.ledger().timestamp(),
-            data,
-        };
-
-        logs.push_back(entry);
-        env.storage().persistent().set(&key, &logs);
+    let logs = client.get_logs(&1);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs.get(0).unwrap().event, LogEvent::Renewal);
+    assert_eq!(logs.get(1).unwrap().event, LogEvent::Cancellation);
+    assert_eq!(client.get_logs(&2).len(), 1);
+    assert_eq!(client.get_logs(&3).len(), 0);
+}
+
+#[test]
+fn test_registered_writer_can_write() {
+    let (env, client, _admin) = setup();
+    let writer = Address::generate(&env);
+    client.add_writer(&writer);
+    assert!(client.is_writer(&writer));
+
+    client.record_log(
+        &writer,
+        &1,
+        &payload(&env, LogEvent::Retry),
+        &String::from_str(&env, "retry"),
+    );
+
+    let logs = client.get_logs(&1);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().writer, writer);
+}
+
+#[test]
+fn test_log_unauthorized_access() {
+    let (env, client, _admin) = setup();
+    let stranger = Address::generate(&env);
+
+    let result = client.try_record_log(
+        &stranger,
+        &1,
+        &payload(&env, LogEvent::Renewal),
+        &String::from_str(&env, "x"),
+    );
+    assert_eq!(result, Err(Ok(Error::UnauthorizedWriter)));
+    assert_eq!(client.get_logs(&1).len(), 0);
+}
+
+#[test]
+fn test_removed_writer_rejected() {
+    let (env, client, _admin) = setup();
+    let writer = Address::generate(&env);
+    client.add_writer(&writer);
+    client.remove_writer(&writer);
+    assert!(!client.is_writer(&writer));
+
+    let result = client.try_record_log(
+        &writer,
+        &1,
+        &payload(&env, LogEvent::Renewal),
+        &String::from_str(&env, "x"),
+    );
+    assert_eq!(result, Err(Ok(Error::UnauthorizedWriter)));
+}
+
+#[test]
+fn test_remove_unknown_writer() {
+    let (env, client, _admin) = setup();
+    let result = client.try_remove_writer(&Address::generate(&env));
+    assert_eq!(result, Err(Ok(Error::WriterNotFound)));
+}
+
+#[test]
+fn test_already_initialized() {
+    let (_env, client, admin) = setup();
+    assert_eq!(client.try_init(&admin), Err(Ok(Error::AlreadyInitialized)));
+}
+
+#[test]
+fn test_log_count_and_pages() {
+    let (env, client, admin) = setup();
+    for _ in 0..5 {
+        client.record_log(
+            &admin,
+            &1,
+            &payload(&env, LogEvent::Renewal),
+            &String::from_str(&env, "r"),
+        );
+    }
+    client.record_log(
+        &admin,
+        &1,
+        &payload(&env, LogEvent::Cancellation),
+        &String::from_str(&env, "c"),
+    );
+
+    assert_eq!(client.get_log_count(&1), 6);
+    assert_eq!(client.get_log_count(&2), 0);
+
+    let first = client.get_logs_page(&1, &0, &4);
+    assert_eq!(first.len(), 4);
+
+    let second = client.get_logs_page(&1, &4, &4);
+    assert_eq!(second.len(), 2);
+    assert_eq!(second.get(1).unwrap().event, LogEvent::Cancellation);
+
+    assert_eq!(client.get_logs_page(&1, &6, &4).len(), 0);
+    assert_eq!(client.get_logs_page(&1, &100, &4).len(), 0);
+}
+
+#[test]
+fn test_page_size_is_clamped() {
+    let (env, client, admin) = setup();
+    for _ in 0..MAX_PAGE_SIZE + 5 {
+        client.record_log(
+            &admin,
+            &1,
+            &payload(&env, LogEvent::Reminder),
+            &String::from_str(&env, "r"),
+        );
+    }
+
+    let page = client.get_logs_page(&1, &0, &(MAX_PAGE_SIZE * 2));
+    assert_eq!(page.len(), MAX_PAGE_SIZE);
+    assert_eq!(client.get_logs(&1).len(), MAX_PAGE_SIZE + 5);
+}
+
+fn policy() -> RetentionPolicy {
+    RetentionPolicy {
+        ttl_threshold: 50_000,
+        ttl_extend_to: 100_000,
     }
+}
+
+#[test]
+fn test_set_retention_rejects_invalid_policy() {
+    let (_env, client, _admin) = setup();
+    let invalid = RetentionPolicy {
+        ttl_threshold: 10,
+        ttl_extend_to: 5,
+    };
+    assert_eq!(
+        client.try_set_retention(&invalid),
+        Err(Ok(Error::InvalidRetentionPolicy))
+    );
+
+    client.set_retention(&policy());
+    assert_eq!(client.get_retention(), Some(policy()));
+}
+
+#[test]
+fn test_retention_keeps_logs_past_default_ttl() {
+    let (env, client, admin) = setup();
+    client.set_retention(&policy());
+    client.record_log(
+        &admin,
+        &1,
+        &payload(&env, LogEvent::Renewal),
+        &String::from_str(&env, "r"),
+    );
+
+    let ttl = env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&DataKey::Log(1, 0))
+    });
+    assert_eq!(ttl, policy().ttl_extend_to);
+
+    let advance = env.ledger().get().min_persistent_entry_ttl + 1_000;
+    env.ledger().with_mut(|li| li.sequence_number += advance);
+
+    let remaining = env.as_contract(&client.address, || {
+        env.storage().persistent().get_ttl(&DataKey::Log(1, 0))
+    });
+    assert_eq!(remaining, policy().ttl_extend_to - advance);
+    assert_eq!(client.get_logs(&1).len(), 1);
+}
 
-    pub fn get_logs(env: Env, sub_id: u64) -> Vec<LogEntry> {
-        let key = DataKey::Logs(sub_id);
+#[test]
+fn test_extend_logs_refreshes_ttl() {
+    let (env, client, admin) = setup();
+    client.record_log(
+        &admin,
+        &1,
+        &payload(&env, LogEvent::Renewal),
+        &String::from_str(&env, "r"),
+    );
+    client.set_retention(&policy());
 
+    client.extend_logs(&1, &0, &10);
+
+    env.as_contract(&client.address, || {
+        for key in [
+            DataKey::Log(1, 0),
+            DataKey::LogHead(1),
+            DataKey::KindLog(1, LogEvent::Renewal, 0),
+            DataKey::KindCount(1, LogEvent::Renewal),
+        ] {
+            assert_eq!(
+                env.storage().persistent().get_ttl(&key),
+                policy().ttl_extend_to
+            );
+        }
+    });
+}
+
+#[test]
+fn test_query_logs_reports_missing_index_entry() {
+    let (env, client, admin) = setup();
+    record_at(&env, &client, &admin, 100, LogEvent::Renewal);
+    env.as_contract(&client.address, || {
         env.storage()
             .persistent()
-            .get(&key)
-            .unwrap_or(Vec::new(&env))
+            .remove(&DataKey::KindLog(1, LogEvent::Renewal, 0));
+    });
+
+    let filter = LogFilter {
+        events: vec![&env, LogEvent::Renewal],
+        from_timestamp: 0,
+        to_timestamp: u64::MAX,
+    };
+    assert_eq!(
+        client.try_query_logs(&1, &filter, &10),
+        Err(Ok(Error::LogEntryMissing))
+    );
+}
+
+#[test]
+fn test_prune_logs_before_timestamp() {
+    let (env, client, admin) = setup();
+    for ts in [100u64, 200, 300, 400] {
+        env.ledger().with_mut(|li| li.timestamp = ts);
+        client.record_log(
+            &admin,
+            &1,
+            &payload(&env, LogEvent::Renewal),
+            &String::from_str(&env, "r"),
+        );
+    }
+
+    let removed = client.prune_logs(&1, &300);
+    assert_eq!(removed, 2);
+    assert_eq!(client.get_log_start(&1), 2);
+    assert_eq!(client.get_log_count(&1), 4);
+
+    let logs = client.get_logs(&1);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs.get(0).unwrap().timestamp, 300);
+    assert_eq!(client.get_logs_page(&1, &0, &2).len(), 0);
+
+    assert_eq!(client.prune_logs(&1, &300), 0);
+}
+
+fn record_at(
+    env: &Env,
+    client: &SubscriptionLoggingContractClient,
+    admin: &Address,
+    ts: u64,
+    event: LogEvent,
+) {
+    env.ledger().with_mut(|li| li.timestamp = ts);
+    client.record_log(admin, &1, &payload(env, event), &String::from_str(env, "e"));
+}
+
+#[test]
+fn test_query_logs_by_kind_and_time() {
+    let (env, client, admin) = setup();
+    record_at(&env, &client, &admin, 100, LogEvent::Renewal);
+    record_at(&env, &client, &admin, 200, LogEvent::Failure);
+    record_at(&env, &client, &admin, 300, LogEvent::Retry);
+    record_at(&env, &client, &admin, 400, LogEvent::Failure);
+    record_at(&env, &client, &admin, 500, LogEvent::Renewal);
+    record_at(&env, &client, &admin, 600, LogEvent::Retry);
+
+    let filter = LogFilter {
+        events: vec![&env, LogEvent::Failure, LogEvent::Retry],
+        from_timestamp: 250,
+        to_timestamp: 550,
+    };
+    let logs = client.query_logs(&1, &filter, &10);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs.get(0).unwrap().timestamp, 300);
+    assert_eq!(logs.get(0).unwrap().event, LogEvent::Retry);
+    assert_eq!(logs.get(1).unwrap().timestamp, 400);
+    assert_eq!(logs.get(1).unwrap().event, LogEvent::Failure);
+}
+
+#[test]
+fn test_query_logs_empty_filter_matches_all_kinds() {
+    let (env, client, admin) = setup();
+    record_at(&env, &client, &admin, 100, LogEvent::Creation);
+    record_at(&env, &client, &admin, 200, LogEvent::Renewal);
+    record_at(&env, &client, &admin, 300, LogEvent::Cancellation);
+
+    let filter = LogFilter {
+        events: vec![&env],
+        from_timestamp: 0,
+        to_timestamp: u64::MAX,
+    };
+    let logs = client.query_logs(&1, &filter, &2);
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs.get(0).unwrap().event, LogEvent::Creation);
+    assert_eq!(logs.get(1).unwrap().event, LogEvent::Renewal);
+}
+
+#[test]
+fn test_get_latest_log() {
+    let (env, client, admin) = setup();
+    assert_eq!(client.get_latest_log(&1, &LogEvent::Renewal), None);
+
+    record_at(&env, &client, &admin, 100, LogEvent::Renewal);
+    record_at(&env, &client, &admin, 200, LogEvent::Failure);
+    record_at(&env, &client, &admin, 300, LogEvent::Renewal);
+    record_at(&env, &client, &admin, 400, LogEvent::Failure);
+
+    let latest = client.get_latest_log(&1, &LogEvent::Renewal).unwrap();
+    assert_eq!(latest.timestamp, 300);
+}
+
+#[test]
+fn test_query_logs_skips_pruned_entries() {
+    let (env, client, admin) = setup();
+    record_at(&env, &client, &admin, 100, LogEvent::Failure);
+    record_at(&env, &client, &admin, 200, LogEvent::Failure);
+    record_at(&env, &client, &admin, 300, LogEvent::Failure);
+
+    client.prune_logs(&1, &250);
+
+    let filter = LogFilter {
+        events: vec![&env, LogEvent::Failure],
+        from_timestamp: 0,
+        to_timestamp: u64::MAX,
+    };
+    let logs = client.query_logs(&1, &filter, &10);
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs.get(0).unwrap().timestamp, 300);
+
+    client.prune_logs(&1, &400);
+    assert_eq!(client.get_latest_log(&1, &LogEvent::Failure), None);
+}
+
+#[test]
+fn test_entries_are_hash_chained() {
+    let (env, client, admin) = setup();
+    let genesis = BytesN::from_array(&env, &[0; 32]);
+    assert_eq!(client.get_log_head(&1), genesis);
+
+    record_at(&env, &client, &admin, 100, LogEvent::Creation);
+    record_at(&env, &client, &admin, 200, LogEvent::Renewal);
+
+    let logs = client.get_logs(&1);
+    let first = logs.get(0).unwrap();
+    let second = logs.get(1).unwrap();
+    let first_hash: BytesN<32> = env.crypto().sha256(&first.clone().to_xdr(&env)).into();
+    let second_hash: BytesN<32> = env.crypto().sha256(&second.clone().to_xdr(&env)).into();
+
+    assert_eq!(first.prev_hash, genesis);
+    assert_eq!(second.prev_hash, first_hash);
+    assert_eq!(client.get_log_head(&1), second_hash);
+    assert_eq!(client.get_log_head(&2), genesis);
+}
+
+#[test]
+fn test_verify_logs() {
+    let (env, client, admin) = setup();
+    for ts in [100u64, 200, 300, 400] {
+        record_at(&env, &client, &admin, ts, LogEvent::Renewal);
+    }
+
+    assert!(client.verify_logs(&1, &0, &4));
+    assert!(client.verify_logs(&1, &1, &3));
+    assert!(!client.verify_logs(&1, &0, &5));
+    assert!(!client.verify_logs(&1, &2, &2));
+    assert!(!client.verify_logs(&1, &3, &1));
+
+    client.prune_logs(&1, &200);
+    assert!(!client.verify_logs(&1, &0, &4));
+    assert!(client.verify_logs(&1, &1, &4));
+}
+
+#[test]
+fn test_verify_logs_detects_tampering() {
+    let (env, client, admin) = setup();
+    for ts in [100u64, 200, 300] {
+        record_at(&env, &client, &admin, ts, LogEvent::Renewal);
     }
+
+    env.as_contract(&client.address, || {
+        let key = DataKey::Log(1, 1);
+        let mut entry: LogEntry = env.storage().persistent().get(&key).unwrap();
+        entry.note = String::from_str(&env, "forged");
+        env.storage().persistent().set(&key, &entry);
+    });
+
+    assert!(!client.verify_logs(&1, &0, &3));
+    assert!(!client.verify_logs(&1, &0, &2));
+    assert!(!client.verify_logs(&1, &1, &2));
+    assert!(client.verify_logs(&1, &0, &1));
+}
+
+#[test]
+fn test_event_is_derived_from_payload() {
+    let (env, client, admin) = setup();
+    let failure = LogPayload::Failure(FailurePayload {
+        reason: 7,
+        failure_count: 2,
+    });
+    client.record_log(&admin, &1, &failure, &String::from_str(&env, ""));
+
+    let entry = client.get_latest_log(&1, &LogEvent::Failure).unwrap();
+    assert_eq!(entry.event, LogEvent::Failure);
+    assert_eq!(entry.payload, failure);
 }
@@ -1,16 +1,76 @@
 #![no_std]
+// `init_sub` takes the full set of billing terms; the lint also fires on the
+// argument structs generated for it.
+#![allow(clippy::too_many_arguments)]
 
 use soroban_sdk::{
-    contract,
-    contractevent,
-    contractimpl,
-    contracttype,
-    xdr::ToXdr,
-    Address,
-    Env,
-    IntoVal,
+    contract, contractclient, contracterror, contractevent, contractimpl, contracttype, token,
+    xdr::ToXdr, Address, BytesN, Env, IntoVal, Map, String, Vec,
 };
 
+pub use billing_calendar::BillingInterval;
+
+/// Failure codes returned by the contract. Codes are stable; new variants
+/// are only ever appended.
+///
+/// `ProtocolPaused`, `NotYetDue`, `OutsideWindow`, `RenewalLockActive`,
+/// `RenewalLockExpired` and `CooldownActive` are transient: the same call can
+/// succeed later. The rest need a change of input or state first.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum RenewalError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    SubscriptionNotFound = 3,
+    LifecycleNotFound = 4,
+    Unauthorized = 5,
+    ProtocolPaused = 6,
+    ApprovalNotFound = 7,
+    NotYetDue = 8,
+    OutsideWindow = 9,
+    SubscriptionFailed = 10,
+    SubscriptionPaused = 11,
+    RenewalLockRequired = 12,
+    RenewalLockExpired = 13,
+    RenewalLockActive = 14,
+    NoRenewalLock = 15,
+    CooldownActive = 16,
+    IntegrityViolation = 17,
+    AmountMismatch = 18,
+    SpendingCapExceeded = 19,
+    InvalidFrequency = 20,
+    InvalidTrialTerms = 21,
+    InvalidRetryPolicy = 22,
+    InvalidPlan = 23,
+    InvalidWindow = 24,
+    InvalidDeadline = 25,
+    NotActive = 26,
+    AlreadyCancelled = 27,
+    AlreadyPaused = 28,
+    NotPaused = 29,
+    NotFailed = 30,
+    NotInGrace = 31,
+    GraceNotOver = 32,
+    NoPendingPriceChange = 33,
+    PriceChangeDeadlinePassed = 34,
+    PriceChangeDeadlineNotReached = 35,
+    PriceChangeConsentExpired = 36,
+    PaymentFailed = 37,
+    TooManyMissedCycles = 38,
+    SubscriptionExists = 39,
+    NotLockHolder = 40,
+    TooManyExecutors = 41,
+    ExecutorNotFound = 42,
+    TooManyApprovals = 43,
+    InvalidLockTimeout = 44,
+    TooManyAgents = 45,
+    AgentNotAllowed = 46,
+    ApprovalUsed = 47,
+    ApprovalExpired = 48,
+    ApprovalExceedsMaxSpend = 49,
+}
+
 #[contracttype]
 #[derive(Clone)]
 enum ContractKey {
@@ -18,6 +78,9 @@ enum ContractKey {
     Paused,
     LoggingContract,
     FeeConfig,
+    DefaultRetryPolicy,
+    NextSubId,
+    AgentRegistry,
 }
 
 #[contracttype]
@@ -27,34 +90,24 @@ struct ApprovalKey {
     approval_id: u64,
 }
 
+/// Ids of approvals on a subscription that have not been consumed yet.
 #[contracttype]
 #[derive(Clone)]
-struct ExecutorKey {
-    sub_id: u64,
-}
-
-#[contracttype]
-#[derive(Clone)]
-struct WindowKey {
-    sub_id: u64,
-}
-
-#[contracttype]
-#[derive(Clone)]
-struct RenewalLockKey {
-    lock_sub_id: u64,
+struct ApprovalsKey {
+    approvals_sub_id: u64,
 }
 
 #[contracttype]
 #[derive(Clone)]
-struct CycleKey {
+struct ExecutorKey {
     sub_id: u64,
 }
 
+/// Registry agents the owner has allowed to act on a subscription.
 #[contracttype]
 #[derive(Clone)]
-struct LifecycleKey {
-    lifecycle_sub_id: u64,
+struct AgentOptInKey {
+    agent_sub_id: u64,
 }
 
 // ── Data types ────────────────────────────────────────────────────────────────
@@ -71,24 +124,93 @@ pub struct RenewalApproval {
 #[contracttype]
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum SubscriptionState {
+    Trialing,
     Active,
     Retrying,
     Failed,
     Cancelled,
+    Paused,
+    /// Payment failed but the grace period is still running: retries
+    /// continue and the owner keeps access.
+    Grace,
+}
+
+/// Optional trial and introductory pricing, fixed at `init_sub`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TrialTerms {
+    /// Seconds from creation until the first charge is due.
+    pub trial_period: u64,
+    /// Price charged for each of the first `intro_cycles` paid cycles.
+    pub intro_amount: i128,
+    pub intro_cycles: u32,
+}
+
+/// How failed renewals are retried, fixed at `init_sub`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPolicy {
+    /// Failed attempts tolerated before the subscription can fail.
+    pub max_retries: u32,
+    /// Ledgers to wait after the first failed attempt.
+    pub cooldown_ledgers: u32,
+    /// Multiplier applied to the cooldown after each further failure.
+    pub backoff_factor: u32,
+    /// Seconds past the due date during which a subscription with a failed
+    /// payment stays in `Grace`, keeping access. It does not fail before the
+    /// grace period ends, even once `max_retries` is exhausted.
+    pub grace_period: u64,
+}
+
+/// What an executor may do on the owner's behalf.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExecutorPermissions {
+    pub can_renew: bool,
+    pub can_lock: bool,
+    /// Allows `set_window`. Windows are public state, so reads need no grant.
+    pub can_manage_window: bool,
+    /// Timestamp after which the grant is ignored; 0 never expires.
+    pub expires_at: u64,
 }
 
+/// Used when neither `init_sub` nor the admin supplies a policy.
+pub const DEFAULT_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    max_retries: 3,
+    cooldown_ledgers: 720,
+    backoff_factor: 2,
+    grace_period: 0,
+};
+
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SubscriptionData {
     pub owner: Address,
     pub merchant: Address,
+    pub token: Address,
     pub amount: i128,
-    pub frequency: u64,
+    pub frequency: BillingInterval,
     pub spending_cap: i128,
-    pub integrity_hash: soroban_sdk::BytesN<32>,
+    pub integrity_hash: BytesN<32>,
     pub state: SubscriptionState,
     pub failure_count: u32,
     pub last_attempt_ledger: u32,
+    /// Cumulative amount successfully charged, bounded by `spending_cap`.
+    pub total_charged: i128,
+    /// Ledger timestamp the current billing cycle becomes renewable. Also
+    /// serves as the cycle id; advanced one `frequency` interval on each
+    /// success.
+    pub next_due: u64,
+    /// All zero when the subscription was created without a trial.
+    pub trial: TrialTerms,
+    pub retry_policy: RetryPolicy,
+    /// Number of successfully charged cycles.
+    pub cycles_charged: u32,
+    /// Price of the cycle currently paid for; the basis for prorating an
+    /// immediate plan change.
+    pub current_price: i128,
+    /// Proration credit deducted from upcoming renewals.
+    pub credit: i128,
 }
 
 #[contracttype]
@@ -98,20 +220,8 @@ pub struct LifecycleTimestamps {
     pub activated_at: u64,
     pub last_renewed_at: u64,
     pub canceled_at: u64,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RenewalWindow {
-    pub billing_start: u64,
-    pub billing_end: u64,
-}
-
-#[contracttype]
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct RenewalLockData {
-    pub locked_at: u32,
-    pub lock_timeout: u32,
+    pub paused_at: u64,
+    pub resumed_at: u64,
 }
 
 // ── Events ────────────────────────────────────────────────────────────────────
@@ -135,6 +245,25 @@ pub struct StateTransition {
     pub new_state: SubscriptionState,
 }
 
+/// Published at creation for subscriptions with a trial, so reminders can be
+/// scheduled ahead of the first charge.
+#[contractevent]
+pub struct TrialEnding {
+    pub sub_id: u64,
+    pub ends_at: u64,
+}
+
+#[contractevent]
+pub struct GracePeriodStarted {
+    pub sub_id: u64,
+    pub ends_at: u64,
+}
+
+#[contractevent]
+pub struct GracePeriodEnded {
+    pub sub_id: u64,
+}
+
 #[contractevent]
 pub struct PauseToggled {
     pub paused: bool,
@@ -149,21 +278,28 @@ pub struct ApprovalCreated {
 }
 
 #[contractevent]
-pub struct ApprovalRejected {
+pub struct ExecutorAssigned {
     pub sub_id: u64,
-    pub approval_id: u64,
-    pub reason: u32,
+    pub executor: Address,
+    pub permissions: ExecutorPermissions,
 }
 
 #[contractevent]
-pub struct ExecutorAssigned {
+pub struct ExecutorRemoved {
     pub sub_id: u64,
     pub executor: Address,
 }
 
 #[contractevent]
-pub struct ExecutorRemoved {
+pub struct AgentAllowed {
+    pub sub_id: u64,
+    pub agent: Address,
+}
+
+#[contractevent]
+pub struct AgentDisallowed {
     pub sub_id: u64,
+    pub agent: Address,
 }
 
 #[contractevent]
@@ -184,13 +320,21 @@ struct RenewalLockKey {
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RenewalLockData {
+    pub holder: Address,
     pub locked_at: u32,
     pub lock_timeout: u32,
 }
 
+impl RenewalLockData {
+    pub fn is_expired(&self, ledger: u32) -> bool {
+        ledger >= self.locked_at.saturating_add(self.lock_timeout)
+    }
+}
+
 #[contractevent]
 pub struct RenewalLockAcquired {
     pub sub_id: u64,
+    pub holder: Address,
     pub locked_at: u32,
     pub lock_timeout: u32,
 }
@@ -223,12 +367,122 @@ pub struct LifecycleTimestampUpdated {
     pub timestamp: u64,
 }
 
+// ── Pause types ───────────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone)]
+struct PauseKey {
+    pause_sub_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PauseData {
+    pub paused_at: u64,
+    /// Time from which anyone may resume the subscription; 0 if only the
+    /// owner can.
+    pub resume_at: u64,
+    /// State restored on resume.
+    pub previous_state: SubscriptionState,
+}
+
+// ── Plan change types ─────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone)]
+struct PlanChangeKey {
+    plan_sub_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PlanChangeEffect {
+    /// Switch now, prorating the remainder of the paid cycle.
+    Immediate,
+    /// Switch when the next renewal is charged.
+    NextRenewal,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PendingPlan {
+    pub amount: i128,
+    pub frequency: BillingInterval,
+}
+
+/// `proration` is positive when it was charged immediately and negative
+/// when it was credited against upcoming renewals.
+#[contractevent]
+pub struct PlanChanged {
+    pub sub_id: u64,
+    pub amount: i128,
+    pub frequency: BillingInterval,
+    pub proration: i128,
+}
+
+#[contractevent]
+pub struct PlanChangeScheduled {
+    pub sub_id: u64,
+    pub amount: i128,
+    pub frequency: BillingInterval,
+}
+
+// ── Price change types ────────────────────────────────────────────
+
+#[contracttype]
+#[derive(Clone)]
+struct PriceChangeKey {
+    price_sub_id: u64,
+}
+
+#[contracttype]
+#[derive(Clone)]
+struct PriceChangePolicyKey {
+    policy_sub_id: u64,
+}
+
+/// What happens to a subscription whose owner does not accept a proposed
+/// price change before its deadline.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PriceChangePolicy {
+    Pause,
+    Cancel,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PriceChangeProposal {
+    pub new_amount: i128,
+    pub deadline: u64,
+}
+
+#[contractevent]
+pub struct PriceChangeProposed {
+    pub sub_id: u64,
+    pub current_amount: i128,
+    pub new_amount: i128,
+    pub deadline: u64,
+}
+
+#[contractevent]
+pub struct PriceChangeAccepted {
+    pub sub_id: u64,
+    pub new_amount: i128,
+}
+
+#[contractevent]
+pub struct PriceChangeLapsed {
+    pub sub_id: u64,
+    pub policy: PriceChangePolicy,
+}
+
 // ── Renewal window types ──────────────────────────────────────────
 
 #[contracttype]
 #[derive(Clone)]
 struct WindowKey {
-    sub_id: u64,
+    window_sub_id: u64,
 }
 
 #[contracttype]
@@ -243,28 +497,130 @@ pub struct RenewalWindow {
 #[contracttype]
 #[derive(Clone)]
 struct CycleKey {
-    sub_id: u64,
+    cycle_sub_id: u64,
 }
 
+// ── Spending cap event ────────────────────────────────────────────
+
+/// Emitted when `renew` declines a charge that would push `total_charged`
+/// past `spending_cap`. The renewal returns `false` rather than failing, so
+/// the approval stays consumed and the event is kept.
 #[contractevent]
-pub struct DuplicateRenewalRejected {
+pub struct SpendingCapExceeded {
     pub sub_id: u64,
+    pub total_charged: i128,
+    pub amount: i128,
+    pub spending_cap: i128,
+}
+
+// ── Logging contract interface ────────────────────────────────────
+
+// Mirrors of the `subscription_logging` payload types this contract writes.
+// Values are encoded by variant and field name, so only the variants used
+// here need to be declared, but names must match the logging contract.
+
+/// `FailurePayload::reason` when the token transfer was rejected.
+pub const FAILURE_REASON_PAYMENT_REJECTED: u32 = 1;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ApprovalPayload {
+    pub approval_id: u64,
+    pub max_spend: i128,
+    pub expires_at: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RenewalPayload {
+    pub amount: i128,
     pub cycle_id: u64,
+    pub ledger: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FailurePayload {
+    pub reason: u32,
+    pub failure_count: u32,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RetryPayload {
+    pub failure_count: u32,
+    pub retry_after_ledger: u32,
 }
 
-// ── Integrity violation event ─────────────────────────────────────
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CancellationPayload {
+    pub canceled_by: Address,
+}
 
-#[contractevent]
-pub struct IntegrityViolation {
-    pub sub_id: u64,
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CreationPayload {
+    pub owner: Address,
+    pub merchant: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub frequency: BillingInterval,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LogPayload {
+    Approval(ApprovalPayload),
+    Renewal(RenewalPayload),
+    Failure(FailurePayload),
+    Retry(RetryPayload),
+    Cancellation(CancellationPayload),
+    Creation(CreationPayload),
+}
+
+#[contractclient(name = "LoggingClient")]
+pub trait LoggingInterface {
+    fn record_log(env: Env, writer: Address, sub_id: u64, payload: LogPayload, note: String);
 }
 
+/// Emitted when the logging contract rejects an entry. Logging is an audit
+/// trail only, so the state change that produced the entry still goes through.
 #[contractevent]
-pub struct LogEmitted {
+pub struct LogWriteFailed {
     pub sub_id: u64,
-    pub event_type: u32,
 }
 
+/// Scopes granted by the agent registry; values match its `Scope` bitmask.
+#[contracttype]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum AgentScope {
+    Renewals = 1,
+    GiftCards = 2,
+    Approvals = 4,
+}
+
+#[contractclient(name = "AgentRegistryClient")]
+pub trait AgentRegistryInterface {
+    fn has_scope(env: Env, agent: Address, scope: AgentScope) -> bool;
+}
+
+/// Upper bound on `lock_timeout`, in ledgers (about an hour).
+pub const MAX_LOCK_TIMEOUT: u32 = 720;
+
+/// Upper bound on unconsumed, unexpired approvals per subscription.
+pub const MAX_PENDING_APPROVALS: u32 = 16;
+
+/// Upper bound on executors assigned to one subscription.
+pub const MAX_EXECUTORS: u32 = 5;
+
+/// Upper bound on registry agents allowed on one subscription.
+pub const MAX_ALLOWED_AGENTS: u32 = 5;
+
+/// Upper bound on cycles `reactivate_sub` settles in one call.
+pub const MAX_MISSED_CYCLES: u32 = 12;
+
 #[contract]
 pub struct SubscriptionRenewalContract;
 
@@ -272,27 +628,30 @@ pub struct SubscriptionRenewalContract;
 impl SubscriptionRenewalContract {
     // ── Admin / Pause management ──────────────────────────────────
 
-    pub fn init(env: Env, admin: Address) {
+    pub fn init(env: Env, admin: Address) -> Result<(), RenewalError> {
         if env.storage().instance().has(&ContractKey::Admin) {
-            panic!("Already initialized");
+            return Err(RenewalError::AlreadyInitialized);
         }
         env.storage().instance().set(&ContractKey::Admin, &admin);
         env.storage().instance().set(&ContractKey::Paused, &false);
+        Ok(())
     }
 
-    fn require_admin(env: &Env) {
+    fn require_admin(env: &Env) -> Result<(), RenewalError> {
         let admin: Address = env
             .storage()
             .instance()
             .get(&ContractKey::Admin)
-            .expect("Contract not initialized");
+            .ok_or(RenewalError::NotInitialized)?;
         admin.require_auth();
+        Ok(())
     }
 
-    pub fn set_paused(env: Env, paused: bool) {
-        Self::require_admin(&env);
+    pub fn set_paused(env: Env, paused: bool) -> Result<(), RenewalError> {
+        Self::require_admin(&env)?;
         env.storage().instance().set(&ContractKey::Paused, &paused);
         PauseToggled { paused }.publish(&env);
+        Ok(())
     }
 
     pub fn is_paused(env: Env) -> bool {
@@ -303,52 +662,148 @@ impl SubscriptionRenewalContract {
     }
 
     /// Set the logging contract address. Admin only.
-    pub fn set_logging_contract(env: Env, address: Address) {
-        Self::require_admin(&env);
+    pub fn set_logging_contract(env: Env, address: Address) -> Result<(), RenewalError> {
+        Self::require_admin(&env)?;
         env.storage()
             .instance()
             .set(&ContractKey::LoggingContract, &address);
+        Ok(())
+    }
+
+    /// Use the agent registry at `address`. An agent only acts on
+    /// subscriptions whose owner allowed it with `allow_agent`: with
+    /// `Renewals` it can lock and renew them, with `Approvals` it can create
+    /// renewal approvals.
+    pub fn set_agent_registry(env: Env, address: Address) -> Result<(), RenewalError> {
+        Self::require_admin(&env)?;
+        env.storage()
+            .instance()
+            .set(&ContractKey::AgentRegistry, &address);
+        Ok(())
+    }
+
+    pub fn get_agent_registry(env: Env) -> Option<Address> {
+        env.storage().instance().get(&ContractKey::AgentRegistry)
+    }
+
+    /// Set the retry policy for subscriptions created without one. Admin
+    /// only; existing subscriptions keep the policy they were created with.
+    pub fn set_default_retry_policy(env: Env, policy: RetryPolicy) -> Result<(), RenewalError> {
+        Self::require_admin(&env)?;
+        if policy.backoff_factor == 0 {
+            return Err(RenewalError::InvalidRetryPolicy);
+        }
+        env.storage()
+            .instance()
+            .set(&ContractKey::DefaultRetryPolicy, &policy);
+        Ok(())
+    }
+
+    pub fn get_default_retry_policy(env: Env) -> RetryPolicy {
+        env.storage()
+            .instance()
+            .get(&ContractKey::DefaultRetryPolicy)
+            .unwrap_or(DEFAULT_RETRY_POLICY)
     }
 
     // ── Subscription management ───────────────────────────────────
 
+    /// Create a subscription billed in `token` (a SEP-41 token contract) and
+    /// return its id. Ids are assigned sequentially from 1; `owner` must
+    /// authorize the call. With `trial`, the subscription starts `Trialing`
+    /// and the first charge is due once the trial period has elapsed.
+    /// Without `retry_policy`, the admin default applies.
     pub fn init_sub(
         env: Env,
         owner: Address,
         merchant: Address,
+        token: Address,
         amount: i128,
-        frequency: u64,
+        frequency: BillingInterval,
         spending_cap: i128,
-        sub_id: u64,
-    ) {
-        let mut integrity_data = soroban_sdk::Vec::<soroban_sdk::Val>::new(&env);
-        integrity_data.push_back(merchant.into_val(&env));
-        integrity_data.push_back(amount.into_val(&env));
-        integrity_data.push_back(frequency.into_val(&env));
-        integrity_data.push_back(spending_cap.into_val(&env));
+        trial: Option<TrialTerms>,
+        retry_policy: Option<RetryPolicy>,
+    ) -> Result<u64, RenewalError> {
+        owner.require_auth();
+
+        if !frequency.is_valid() {
+            return Err(RenewalError::InvalidFrequency);
+        }
+        if let Some(ref terms) = trial {
+            if terms.intro_amount < 0 {
+                return Err(RenewalError::InvalidTrialTerms);
+            }
+        }
+        let retry_policy =
+            retry_policy.unwrap_or_else(|| Self::get_default_retry_policy(env.clone()));
+        if retry_policy.backoff_factor == 0 {
+            return Err(RenewalError::InvalidRetryPolicy);
+        }
 
-        let integrity_hash = env.crypto().sha256(&integrity_data.to_xdr(&env));
+        let now = env.ledger().timestamp();
+        let trial = trial.unwrap_or(TrialTerms {
+            trial_period: 0,
+            intro_amount: 0,
+            intro_cycles: 0,
+        });
+        let trial_period = trial.trial_period;
+        let next_due = now
+            .checked_add(trial_period)
+            .ok_or(RenewalError::InvalidTrialTerms)?;
+        let state = if trial_period > 0 {
+            SubscriptionState::Trialing
+        } else {
+            SubscriptionState::Active
+        };
+
+        let sub_id: u64 = env
+            .storage()
+            .instance()
+            .get(&ContractKey::NextSubId)
+            .unwrap_or(1);
+        if env.storage().persistent().has(&sub_id) {
+            return Err(RenewalError::SubscriptionExists);
+        }
+        env.storage()
+            .instance()
+            .set(&ContractKey::NextSubId, &(sub_id + 1));
 
         let key = sub_id;
-        let data = SubscriptionData {
+        let mut data = SubscriptionData {
             owner,
             merchant,
+            token,
             amount,
             frequency,
             spending_cap,
-            integrity_hash: integrity_hash.into(),
-            state: SubscriptionState::Active,
+            integrity_hash: BytesN::from_array(&env, &[0; 32]),
+            state,
             failure_count: 0,
             last_attempt_ledger: 0,
+            total_charged: 0,
+            next_due,
+            trial,
+            retry_policy,
+            cycles_charged: 0,
+            current_price: 0,
+            credit: 0,
         };
+        data.integrity_hash = Self::compute_integrity_hash(&env, &data);
         env.storage().persistent().set(&key, &data);
 
-        let now = env.ledger().timestamp();
+        // A trialing subscription is activated by its first charge.
+        let activated_at = if state == SubscriptionState::Trialing {
+            0
+        } else {
+            now
+        };
         let lifecycle = LifecycleTimestamps {
             created_at: now,
-            activated_at: now,
+            activated_at,
             last_renewed_at: 0,
             canceled_at: 0,
+            paused_at: 0,
+            resumed_at: 0,
         };
         let lc_key = LifecycleKey {
             lifecycle_sub_id: sub_id,
@@ -361,62 +816,108 @@ impl SubscriptionRenewalContract {
             timestamp: now,
         }
         .publish(&env);
-        LifecycleTimestampUpdated {
-            sub_id,
-            event_kind: 2,
-            timestamp: now,
+        if state == SubscriptionState::Trialing {
+            TrialEnding {
+                sub_id,
+                ends_at: data.next_due,
+            }
+            .publish(&env);
+        } else {
+            LifecycleTimestampUpdated {
+                sub_id,
+                event_kind: 2,
+                timestamp: now,
+            }
+            .publish(&env);
         }
-        .publish(&env);
 
         Self::record_log(
             &env,
             sub_id,
-            2,
-            soroban_sdk::String::from_str(&env, "Subscription initialized"),
+            LogPayload::Creation(CreationPayload {
+                owner: data.owner.clone(),
+                merchant: data.merchant.clone(),
+                token: data.token.clone(),
+                amount: data.amount,
+                frequency: data.frequency.clone(),
+            }),
+            String::from_str(&env, "Subscription initialized"),
         );
+        Ok(sub_id)
     }
 
-    fn record_log(env: &Env, sub_id: u64, event_type: u32, _data_str: soroban_sdk::String) {
-        if let Some(_log_addr) = env
+    /// Append an entry to the logging contract, if one is configured. The
+    /// logging contract must have this contract registered as a writer.
+    fn record_log(env: &Env, sub_id: u64, payload: LogPayload, note: String) {
+        if let Some(log_addr) = env
             .storage()
             .instance()
             .get::<_, Address>(&ContractKey::LoggingContract)
         {
-            // Here we would call the logging contract.
-            // Since we are in a multi-contract setup, we'd use a client.
-            // For now, we'll emit an event as a placeholder or assume the client is available.
-            // (In a real implementation, we'd use a cross-contract call).
-            LogEmitted { sub_id, event_type }.publish(env);
+            let result = LoggingClient::new(env, &log_addr).try_record_log(
+                &env.current_contract_address(),
+                &sub_id,
+                &payload,
+                &note,
+            );
+            if !matches!(result, Ok(Ok(()))) {
+                LogWriteFailed { sub_id }.publish(env);
+            }
         }
     }
 
     /// Explicitly cancel a subscription
-    pub fn cancel_sub(env: Env, sub_id: u64) {
+    pub fn cancel_sub(env: Env, sub_id: u64) -> Result<(), RenewalError> {
         let key = sub_id;
-        let mut data: SubscriptionData = env
+        let data: SubscriptionData = env
             .storage()
             .persistent()
             .get(&key)
-            .expect("Subscription not found");
+            .ok_or(RenewalError::SubscriptionNotFound)?;
 
         data.owner.require_auth();
 
         if data.state == SubscriptionState::Cancelled {
-            panic!("Subscription already cancelled");
+            return Err(RenewalError::AlreadyCancelled);
         }
 
+        Self::mark_cancelled(&env, sub_id, data)?;
+        Ok(())
+    }
+
+    fn mark_cancelled(
+        env: &Env,
+        sub_id: u64,
+        mut data: SubscriptionData,
+    ) -> Result<(), RenewalError> {
         data.state = SubscriptionState::Cancelled;
-        env.storage().persistent().set(&key, &data);
+        env.storage().persistent().set(&sub_id, &data);
 
-        let lc_key = LifecycleKey {
-            lifecycle_sub_id: sub_id,
+        let approvals_key = ApprovalsKey {
+            approvals_sub_id: sub_id,
         };
-        let mut lifecycle: LifecycleTimestamps = env
+        let pending: Vec<u64> = env
             .storage()
             .persistent()
-            .get(&lc_key)
-            .expect("Lifecycle data not found");
-        let now = env.ledger().timestamp();
+            .get(&approvals_key)
+            .unwrap_or(Vec::new(env));
+        for approval_id in pending.iter() {
+            env.storage().persistent().remove(&ApprovalKey {
+                sub_id,
+                approval_id,
+            });
+        }
+        env.storage().persistent().remove(&approvals_key);
+
+        let lc_key = LifecycleKey {
+            lifecycle_sub_id: sub_id,
+        };
+        let mut lifecycle: LifecycleTimestamps = env
+            .storage()
+            .persistent()
+            .get(&lc_key)
+            .ok_or(RenewalError::LifecycleNotFound)?;
+        let now = env.ledger().timestamp();
         lifecycle.canceled_at = now;
         env.storage().persistent().set(&lc_key, &lifecycle);
 
@@ -425,89 +926,501 @@ impl SubscriptionRenewalContract {
             event_kind: 4,
             timestamp: now,
         }
-        .publish(&env);
+        .publish(env);
 
         Self::record_log(
-            &env,
+            env,
             sub_id,
-            5,
-            soroban_sdk::String::from_str(&env, "Subscription cancelled"),
+            LogPayload::Cancellation(CancellationPayload {
+                canceled_by: data.owner.clone(),
+            }),
+            String::from_str(env, "Subscription cancelled"),
         );
 
         StateTransition {
             sub_id,
             new_state: SubscriptionState::Cancelled,
         }
-        .publish(&env);
+        .publish(env);
+        Ok(())
     }
 
-    pub fn get_sub(env: Env, sub_id: u64) -> SubscriptionData {
+    pub fn get_sub(env: Env, sub_id: u64) -> Result<SubscriptionData, RenewalError> {
         env.storage()
             .persistent()
             .get(&sub_id)
-            .expect("Subscription not found")
+            .ok_or(RenewalError::SubscriptionNotFound)
+    }
+
+    /// Amount the next renewal of `sub_id` must charge, including any plan
+    /// change scheduled for it.
+    pub fn get_due_amount(env: Env, sub_id: u64) -> Result<i128, RenewalError> {
+        let mut data = Self::get_sub(env.clone(), sub_id)?;
+        if let Some(plan) = Self::get_pending_plan(env, sub_id) {
+            data.amount = plan.amount;
+        }
+        Ok(Self::due_amount(&data))
     }
 
-    pub fn get_lifecycle(env: Env, sub_id: u64) -> LifecycleTimestamps {
+    pub fn get_lifecycle(env: Env, sub_id: u64) -> Result<LifecycleTimestamps, RenewalError> {
         let lc_key = LifecycleKey {
             lifecycle_sub_id: sub_id,
         };
         env.storage()
             .persistent()
             .get(&lc_key)
-            .expect("Lifecycle data not found")
+            .ok_or(RenewalError::LifecycleNotFound)
+    }
+
+    // ── Dunning ───────────────────────────────────────────────────
+
+    /// Whether the owner should currently have access: during a trial or
+    /// paid cycle, and through the grace period after a missed payment. A
+    /// cancelled subscription keeps access until the paid cycle ends.
+    pub fn is_entitled(env: Env, sub_id: u64) -> Result<bool, RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        let now = env.ledger().timestamp();
+        let entitled = match data.state {
+            SubscriptionState::Active | SubscriptionState::Trialing | SubscriptionState::Grace => {
+                now < data.next_due.saturating_add(data.retry_policy.grace_period)
+            }
+            SubscriptionState::Cancelled => now < data.next_due,
+            SubscriptionState::Retrying | SubscriptionState::Failed | SubscriptionState::Paused => {
+                false
+            }
+        };
+        Ok(entitled)
+    }
+
+    /// Move a subscription out of `Grace` once its grace period has ended
+    /// without a successful renewal: to `Failed` if its retries are
+    /// exhausted, otherwise to `Retrying`. Callable by anyone.
+    pub fn end_grace(env: Env, sub_id: u64) -> Result<(), RenewalError> {
+        let mut data = Self::get_sub(env.clone(), sub_id)?;
+        if data.state != SubscriptionState::Grace {
+            return Err(RenewalError::NotInGrace);
+        }
+        if env.ledger().timestamp() < data.next_due.saturating_add(data.retry_policy.grace_period) {
+            return Err(RenewalError::GraceNotOver);
+        }
+
+        let new_state = if data.failure_count > data.retry_policy.max_retries {
+            SubscriptionState::Failed
+        } else {
+            SubscriptionState::Retrying
+        };
+        data.state = new_state;
+        env.storage().persistent().set(&sub_id, &data);
+
+        GracePeriodEnded { sub_id }.publish(&env);
+        StateTransition { sub_id, new_state }.publish(&env);
+
+        if new_state == SubscriptionState::Failed {
+            Self::record_log(
+                &env,
+                sub_id,
+                LogPayload::Failure(FailurePayload {
+                    reason: FAILURE_REASON_PAYMENT_REJECTED,
+                    failure_count: data.failure_count,
+                }),
+                String::from_str(&env, "Grace period ended - max retries exceeded"),
+            );
+        }
+        Ok(())
+    }
+
+    // ── Recovery ──────────────────────────────────────────────────
+
+    /// Bring a `Failed` subscription back to `Active`. With `settle_missed`,
+    /// every cycle that fell due in the meantime is charged now; otherwise
+    /// those cycles are skipped and a new cycle is due immediately.
+    pub fn reactivate_sub(env: Env, sub_id: u64, settle_missed: bool) -> Result<(), RenewalError> {
+        if Self::is_paused(env.clone()) {
+            return Err(RenewalError::ProtocolPaused);
+        }
+
+        let mut data = Self::get_sub(env.clone(), sub_id)?;
+        data.owner.require_auth();
+
+        if data.state != SubscriptionState::Failed {
+            return Err(RenewalError::NotFailed);
+        }
+        if Self::compute_integrity_hash(&env, &data) != data.integrity_hash {
+            return Err(RenewalError::IntegrityViolation);
+        }
+
+        let now = env.ledger().timestamp();
+        let cycle_key = CycleKey {
+            cycle_sub_id: sub_id,
+        };
+        let mut settled = 0;
+        if settle_missed {
+            // Missed cycles are charged like renewals: on the latest plan and
+            // only once any price change has been answered.
+            Self::check_price_change_consent(&env, sub_id)?;
+            Self::apply_pending_plan(&env, sub_id, &mut data);
+
+            while data.next_due <= now {
+                if settled == MAX_MISSED_CYCLES {
+                    return Err(RenewalError::TooManyMissedCycles);
+                }
+                let amount = Self::due_amount(&data);
+                if data.total_charged + amount > data.spending_cap {
+                    return Err(RenewalError::SpendingCapExceeded);
+                }
+                if !Self::settle(&env, &data, amount) {
+                    return Err(RenewalError::PaymentFailed);
+                }
+
+                let cycle_id = data.next_due;
+                Self::record_charge(&mut data, amount, cycle_id);
+                env.storage().persistent().set(&cycle_key, &cycle_id);
+                settled += 1;
+
+                Self::record_log(
+                    &env,
+                    sub_id,
+                    LogPayload::Renewal(RenewalPayload {
+                        amount,
+                        cycle_id,
+                        ledger: env.ledger().sequence(),
+                    }),
+                    String::from_str(&env, "Missed cycle settled"),
+                );
+            }
+        } else {
+            data.next_due = now;
+        }
+
+        data.state = SubscriptionState::Active;
+        data.failure_count = 0;
+        env.storage().persistent().set(&sub_id, &data);
+
+        let lc_key = LifecycleKey {
+            lifecycle_sub_id: sub_id,
+        };
+        let mut lifecycle: LifecycleTimestamps = env
+            .storage()
+            .persistent()
+            .get(&lc_key)
+            .ok_or(RenewalError::LifecycleNotFound)?;
+        lifecycle.activated_at = now;
+        if settled > 0 {
+            lifecycle.last_renewed_at = now;
+            LifecycleTimestampUpdated {
+                sub_id,
+                event_kind: 3,
+                timestamp: now,
+            }
+            .publish(&env);
+        }
+        env.storage().persistent().set(&lc_key, &lifecycle);
+
+        LifecycleTimestampUpdated {
+            sub_id,
+            event_kind: 2,
+            timestamp: now,
+        }
+        .publish(&env);
+        StateTransition {
+            sub_id,
+            new_state: SubscriptionState::Active,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    // ── Pause / resume ────────────────────────────────────────────
+
+    /// Pause a single subscription. Renewals are rejected until it is
+    /// resumed; with `resume_at`, anyone may resume it from that time.
+    pub fn pause_sub(env: Env, sub_id: u64, resume_at: Option<u64>) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        data.owner.require_auth();
+
+        match data.state {
+            SubscriptionState::Paused => return Err(RenewalError::AlreadyPaused),
+            SubscriptionState::Cancelled | SubscriptionState::Failed => {
+                return Err(RenewalError::NotActive)
+            }
+            _ => {}
+        }
+        let resume_at = resume_at.unwrap_or(0);
+        if resume_at != 0 && resume_at <= env.ledger().timestamp() {
+            return Err(RenewalError::InvalidDeadline);
+        }
+
+        Self::mark_paused(&env, sub_id, data, resume_at)?;
+        Ok(())
+    }
+
+    /// Resume a paused subscription, pushing `next_due` back by the time it
+    /// spent paused. Owner only until the scheduled `resume_at`.
+    pub fn resume_sub(env: Env, sub_id: u64) -> Result<(), RenewalError> {
+        let mut data = Self::get_sub(env.clone(), sub_id)?;
+        if data.state != SubscriptionState::Paused {
+            return Err(RenewalError::NotPaused);
+        }
+
+        let pause_key = PauseKey {
+            pause_sub_id: sub_id,
+        };
+        let pause: PauseData = env
+            .storage()
+            .persistent()
+            .get(&pause_key)
+            .ok_or(RenewalError::NotPaused)?;
+
+        let now = env.ledger().timestamp();
+        if pause.resume_at == 0 || now < pause.resume_at {
+            data.owner.require_auth();
+        }
+
+        data.state = pause.previous_state;
+        data.next_due = data.next_due.saturating_add(now - pause.paused_at);
+        env.storage().persistent().set(&sub_id, &data);
+        env.storage().persistent().remove(&pause_key);
+
+        let lc_key = LifecycleKey {
+            lifecycle_sub_id: sub_id,
+        };
+        let mut lifecycle: LifecycleTimestamps = env
+            .storage()
+            .persistent()
+            .get(&lc_key)
+            .ok_or(RenewalError::LifecycleNotFound)?;
+        lifecycle.resumed_at = now;
+        env.storage().persistent().set(&lc_key, &lifecycle);
+
+        LifecycleTimestampUpdated {
+            sub_id,
+            event_kind: 6,
+            timestamp: now,
+        }
+        .publish(&env);
+        StateTransition {
+            sub_id,
+            new_state: data.state,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_pause(env: Env, sub_id: u64) -> Option<PauseData> {
+        let pause_key = PauseKey {
+            pause_sub_id: sub_id,
+        };
+        env.storage().persistent().get(&pause_key)
+    }
+
+    fn mark_paused(
+        env: &Env,
+        sub_id: u64,
+        mut data: SubscriptionData,
+        resume_at: u64,
+    ) -> Result<(), RenewalError> {
+        let now = env.ledger().timestamp();
+        let pause_key = PauseKey {
+            pause_sub_id: sub_id,
+        };
+        env.storage().persistent().set(
+            &pause_key,
+            &PauseData {
+                paused_at: now,
+                resume_at,
+                previous_state: data.state,
+            },
+        );
+
+        data.state = SubscriptionState::Paused;
+        env.storage().persistent().set(&sub_id, &data);
+
+        let lc_key = LifecycleKey {
+            lifecycle_sub_id: sub_id,
+        };
+        let mut lifecycle: LifecycleTimestamps = env
+            .storage()
+            .persistent()
+            .get(&lc_key)
+            .ok_or(RenewalError::LifecycleNotFound)?;
+        lifecycle.paused_at = now;
+        env.storage().persistent().set(&lc_key, &lifecycle);
+
+        LifecycleTimestampUpdated {
+            sub_id,
+            event_kind: 5,
+            timestamp: now,
+        }
+        .publish(env);
+        StateTransition {
+            sub_id,
+            new_state: SubscriptionState::Paused,
+        }
+        .publish(env);
+        Ok(())
     }
 
     // ── Executor management ───────────────────────────────────────
 
-    pub fn set_executor(env: Env, sub_id: u64, executor: Address) {
+    /// Grant `executor` the given permissions on `sub_id`, replacing any
+    /// earlier grant. At most `MAX_EXECUTORS` executors per subscription.
+    pub fn set_executor(
+        env: Env,
+        sub_id: u64,
+        executor: Address,
+        permissions: ExecutorPermissions,
+    ) -> Result<(), RenewalError> {
         let data: SubscriptionData = env
             .storage()
             .persistent()
             .get(&sub_id)
-            .expect("Subscription not found");
+            .ok_or(RenewalError::SubscriptionNotFound)?;
 
         data.owner.require_auth();
 
         let key = ExecutorKey { sub_id };
-        env.storage().persistent().set(&key, &executor);
+        let mut executors: Map<Address, ExecutorPermissions> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Map::new(&env));
+        if !executors.contains_key(executor.clone()) && executors.len() >= MAX_EXECUTORS {
+            return Err(RenewalError::TooManyExecutors);
+        }
+        executors.set(executor.clone(), permissions.clone());
+        env.storage().persistent().set(&key, &executors);
 
-        ExecutorAssigned { sub_id, executor }.publish(&env);
+        ExecutorAssigned {
+            sub_id,
+            executor,
+            permissions,
+        }
+        .publish(&env);
+        Ok(())
     }
 
-    pub fn remove_executor(env: Env, sub_id: u64) {
+    pub fn remove_executor(env: Env, sub_id: u64, executor: Address) -> Result<(), RenewalError> {
         let data: SubscriptionData = env
             .storage()
             .persistent()
             .get(&sub_id)
-            .expect("Subscription not found");
+            .ok_or(RenewalError::SubscriptionNotFound)?;
 
         data.owner.require_auth();
 
         let key = ExecutorKey { sub_id };
-        env.storage().persistent().remove(&key);
+        let mut executors: Map<Address, ExecutorPermissions> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(RenewalError::ExecutorNotFound)?;
+        if executors.remove(executor.clone()).is_none() {
+            return Err(RenewalError::ExecutorNotFound);
+        }
+        if executors.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &executors);
+        }
+
+        ExecutorRemoved { sub_id, executor }.publish(&env);
+        Ok(())
+    }
 
-        ExecutorRemoved { sub_id }.publish(&env);
+    pub fn get_executor(env: Env, sub_id: u64, executor: Address) -> Option<ExecutorPermissions> {
+        Self::get_executors(env, sub_id).get(executor)
     }
 
-    pub fn get_executor(env: Env, sub_id: u64) -> Option<Address> {
+    pub fn get_executors(env: Env, sub_id: u64) -> Map<Address, ExecutorPermissions> {
         let key = ExecutorKey { sub_id };
-        env.storage().persistent().get(&key)
+        env.storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(Map::new(&env))
+    }
+
+    // ── Agent opt-in ──────────────────────────────────────────────
+
+    /// Let registry `agent` act on `sub_id` within the scopes the registry
+    /// grants it. Owner only; at most `MAX_ALLOWED_AGENTS` per subscription.
+    pub fn allow_agent(env: Env, sub_id: u64, agent: Address) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        data.owner.require_auth();
+
+        let mut agents = Self::get_allowed_agents(env.clone(), sub_id);
+        if agents.contains(&agent) {
+            return Ok(());
+        }
+        if agents.len() >= MAX_ALLOWED_AGENTS {
+            return Err(RenewalError::TooManyAgents);
+        }
+        agents.push_back(agent.clone());
+        env.storage().persistent().set(
+            &AgentOptInKey {
+                agent_sub_id: sub_id,
+            },
+            &agents,
+        );
+
+        AgentAllowed { sub_id, agent }.publish(&env);
+        Ok(())
+    }
+
+    pub fn disallow_agent(env: Env, sub_id: u64, agent: Address) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        data.owner.require_auth();
+
+        let key = AgentOptInKey {
+            agent_sub_id: sub_id,
+        };
+        let mut agents = Self::get_allowed_agents(env.clone(), sub_id);
+        let index = agents
+            .first_index_of(&agent)
+            .ok_or(RenewalError::AgentNotAllowed)?;
+        agents.remove(index);
+        if agents.is_empty() {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().persistent().set(&key, &agents);
+        }
+
+        AgentDisallowed { sub_id, agent }.publish(&env);
+        Ok(())
+    }
+
+    pub fn get_allowed_agents(env: Env, sub_id: u64) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&AgentOptInKey {
+                agent_sub_id: sub_id,
+            })
+            .unwrap_or(Vec::new(&env))
     }
 
     // ── Renewal window management ─────────────────────────────────
 
-    pub fn set_window(env: Env, sub_id: u64, billing_start: u64, billing_end: u64) {
+    /// Restrict renewals of `sub_id` to a time window. `caller` must be the
+    /// owner or an executor allowed to manage the window.
+    pub fn set_window(
+        env: Env,
+        caller: Address,
+        sub_id: u64,
+        billing_start: u64,
+        billing_end: u64,
+    ) -> Result<(), RenewalError> {
         let data: SubscriptionData = env
             .storage()
             .persistent()
             .get(&sub_id)
-            .expect("Subscription not found");
+            .ok_or(RenewalError::SubscriptionNotFound)?;
 
-        data.owner.require_auth();
+        caller.require_auth();
+        if !Self::is_permitted(&env, sub_id, &data, &caller, |p| p.can_manage_window) {
+            return Err(RenewalError::Unauthorized);
+        }
 
         if billing_start >= billing_end {
-            panic!("Invalid window: start must be before end");
+            return Err(RenewalError::InvalidWindow);
         }
 
         let window = RenewalWindow {
@@ -515,7 +1428,9 @@ impl SubscriptionRenewalContract {
             billing_end,
         };
 
-        let key = WindowKey { sub_id };
+        let key = WindowKey {
+            window_sub_id: sub_id,
+        };
         env.storage().persistent().set(&key, &window);
 
         WindowUpdated {
@@ -524,30 +1439,352 @@ impl SubscriptionRenewalContract {
             billing_end,
         }
         .publish(&env);
+        Ok(())
     }
 
     pub fn get_window(env: Env, sub_id: u64) -> Option<RenewalWindow> {
-        let key = WindowKey { sub_id };
+        let key = WindowKey {
+            window_sub_id: sub_id,
+        };
+        env.storage().persistent().get(&key)
+    }
+
+    // ── Plan changes ──────────────────────────────────────────────
+
+    /// Move the subscription to a new price and billing interval. Requires
+    /// both owner and merchant consent. An `Immediate` change charges or
+    /// credits the difference for the rest of the paid cycle; a
+    /// `NextRenewal` change takes effect when the next cycle is charged.
+    pub fn change_plan(
+        env: Env,
+        sub_id: u64,
+        new_amount: i128,
+        new_frequency: BillingInterval,
+        effective: PlanChangeEffect,
+    ) -> Result<(), RenewalError> {
+        let key = sub_id;
+        let mut data: SubscriptionData = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(RenewalError::SubscriptionNotFound)?;
+
+        data.owner.require_auth();
+        data.merchant.require_auth();
+
+        if data.state == SubscriptionState::Cancelled || data.state == SubscriptionState::Failed {
+            return Err(RenewalError::NotActive);
+        }
+        if new_amount <= 0 || !new_frequency.is_valid() {
+            return Err(RenewalError::InvalidPlan);
+        }
+        if Self::compute_integrity_hash(&env, &data) != data.integrity_hash {
+            return Err(RenewalError::IntegrityViolation);
+        }
+
+        let plan_key = PlanChangeKey {
+            plan_sub_id: sub_id,
+        };
+
+        if effective == PlanChangeEffect::NextRenewal {
+            env.storage().persistent().set(
+                &plan_key,
+                &PendingPlan {
+                    amount: new_amount,
+                    frequency: new_frequency.clone(),
+                },
+            );
+            PlanChangeScheduled {
+                sub_id,
+                amount: new_amount,
+                frequency: new_frequency,
+            }
+            .publish(&env);
+            return Ok(());
+        }
+
+        let mut proration = 0;
+        let cycle_key = CycleKey {
+            cycle_sub_id: sub_id,
+        };
+        let now = env.ledger().timestamp();
+        if let Some(cycle_start) = env.storage().persistent().get::<_, u64>(&cycle_key) {
+            if now < data.next_due {
+                // Value of the unused part of the paid cycle under each plan.
+                let remaining = (data.next_due - now) as i128;
+                let old_len = (data.next_due - cycle_start) as i128;
+                let new_len = (new_frequency.next_due(cycle_start) - cycle_start) as i128;
+                let new_price = new_amount * old_len / new_len;
+                proration = (new_price - data.current_price) * remaining / old_len;
+                data.current_price = new_price;
+            }
+        }
+
+        if proration > 0 {
+            if Self::is_paused(env.clone()) {
+                return Err(RenewalError::ProtocolPaused);
+            }
+            if data.total_charged + proration > data.spending_cap {
+                return Err(RenewalError::SpendingCapExceeded);
+            }
+            if !Self::settle(&env, &data, proration) {
+                return Err(RenewalError::PaymentFailed);
+            }
+            data.total_charged += proration;
+        } else {
+            data.credit -= proration;
+        }
+
+        data.amount = new_amount;
+        data.frequency = new_frequency.clone();
+        data.integrity_hash = Self::compute_integrity_hash(&env, &data);
+        env.storage().persistent().set(&key, &data);
+        env.storage().persistent().remove(&plan_key);
+
+        PlanChanged {
+            sub_id,
+            amount: new_amount,
+            frequency: new_frequency,
+            proration,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    pub fn get_pending_plan(env: Env, sub_id: u64) -> Option<PendingPlan> {
+        let plan_key = PlanChangeKey {
+            plan_sub_id: sub_id,
+        };
+        env.storage().persistent().get(&plan_key)
+    }
+
+    // ── Merchant price changes ────────────────────────────────────
+
+    /// Choose what happens if a proposed price change is not accepted in
+    /// time. Defaults to `Pause`.
+    pub fn set_price_change_policy(
+        env: Env,
+        sub_id: u64,
+        policy: PriceChangePolicy,
+    ) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        data.owner.require_auth();
+
+        let key = PriceChangePolicyKey {
+            policy_sub_id: sub_id,
+        };
+        env.storage().persistent().set(&key, &policy);
+        Ok(())
+    }
+
+    pub fn get_price_change_policy(env: Env, sub_id: u64) -> PriceChangePolicy {
+        let key = PriceChangePolicyKey {
+            policy_sub_id: sub_id,
+        };
+        env.storage()
+            .persistent()
+            .get(&key)
+            .unwrap_or(PriceChangePolicy::Pause)
+    }
+
+    /// Propose a new price. It applies from the next renewal once the owner
+    /// accepts it, which must happen by `deadline`. Replaces any earlier
+    /// proposal.
+    pub fn propose_price_change(
+        env: Env,
+        sub_id: u64,
+        new_amount: i128,
+        deadline: u64,
+    ) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        data.merchant.require_auth();
+
+        if data.state == SubscriptionState::Cancelled || data.state == SubscriptionState::Failed {
+            return Err(RenewalError::NotActive);
+        }
+        if new_amount <= 0 {
+            return Err(RenewalError::InvalidPlan);
+        }
+        if deadline <= env.ledger().timestamp() {
+            return Err(RenewalError::InvalidDeadline);
+        }
+
+        let key = PriceChangeKey {
+            price_sub_id: sub_id,
+        };
+        env.storage().persistent().set(
+            &key,
+            &PriceChangeProposal {
+                new_amount,
+                deadline,
+            },
+        );
+
+        PriceChangeProposed {
+            sub_id,
+            current_amount: data.amount,
+            new_amount,
+            deadline,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Accept the pending price change, scheduling it for the next renewal.
+    /// A plan change already scheduled for the next renewal keeps its
+    /// frequency and takes the new price.
+    pub fn accept_price_change(env: Env, sub_id: u64) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+        data.owner.require_auth();
+
+        let key = PriceChangeKey {
+            price_sub_id: sub_id,
+        };
+        let proposal: PriceChangeProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(RenewalError::NoPendingPriceChange)?;
+        if env.ledger().timestamp() > proposal.deadline {
+            return Err(RenewalError::PriceChangeDeadlinePassed);
+        }
+        env.storage().persistent().remove(&key);
+
+        let plan_key = PlanChangeKey {
+            plan_sub_id: sub_id,
+        };
+        let frequency = env
+            .storage()
+            .persistent()
+            .get::<PlanChangeKey, PendingPlan>(&plan_key)
+            .map_or(data.frequency, |plan| plan.frequency);
+        env.storage().persistent().set(
+            &plan_key,
+            &PendingPlan {
+                amount: proposal.new_amount,
+                frequency: frequency.clone(),
+            },
+        );
+
+        PriceChangeAccepted {
+            sub_id,
+            new_amount: proposal.new_amount,
+        }
+        .publish(&env);
+        PlanChangeScheduled {
+            sub_id,
+            amount: proposal.new_amount,
+            frequency,
+        }
+        .publish(&env);
+        Ok(())
+    }
+
+    /// Apply the owner's price change policy to a proposal whose deadline
+    /// passed without acceptance. Callable by anyone.
+    pub fn expire_price_change(env: Env, sub_id: u64) -> Result<(), RenewalError> {
+        let data = Self::get_sub(env.clone(), sub_id)?;
+
+        let key = PriceChangeKey {
+            price_sub_id: sub_id,
+        };
+        let proposal: PriceChangeProposal = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(RenewalError::NoPendingPriceChange)?;
+        if env.ledger().timestamp() <= proposal.deadline {
+            return Err(RenewalError::PriceChangeDeadlineNotReached);
+        }
+        env.storage().persistent().remove(&key);
+
+        let policy = Self::get_price_change_policy(env.clone(), sub_id);
+        PriceChangeLapsed { sub_id, policy }.publish(&env);
+
+        if data.state == SubscriptionState::Cancelled {
+            return Ok(());
+        }
+        match policy {
+            PriceChangePolicy::Cancel => Self::mark_cancelled(&env, sub_id, data)?,
+            PriceChangePolicy::Pause => {
+                if data.state != SubscriptionState::Paused {
+                    Self::mark_paused(&env, sub_id, data, 0)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_price_change(env: Env, sub_id: u64) -> Option<PriceChangeProposal> {
+        let key = PriceChangeKey {
+            price_sub_id: sub_id,
+        };
         env.storage().persistent().get(&key)
     }
 
     // ── Approval management ───────────────────────────────────────
 
+    /// Approve one renewal of `sub_id`. `caller` must be the owner or an
+    /// allowed agent holding `AgentScope::Approvals`.
     pub fn approve_renewal(
         env: Env,
+        caller: Address,
         sub_id: u64,
         approval_id: u64,
         max_spend: i128,
         expires_at: u32,
-    ) {
+    ) -> Result<(), RenewalError> {
         let sub_key = sub_id;
         let data: SubscriptionData = env
             .storage()
             .persistent()
             .get(&sub_key)
-            .expect("Subscription not found");
+            .ok_or(RenewalError::SubscriptionNotFound)?;
 
-        data.owner.require_auth();
+        caller.require_auth();
+        if caller != data.owner && !Self::is_agent(&env, sub_id, &caller, AgentScope::Approvals) {
+            return Err(RenewalError::Unauthorized);
+        }
+
+        if data.state == SubscriptionState::Cancelled {
+            return Err(RenewalError::NotActive);
+        }
+
+        // Drop consumed or expired approvals before enforcing the bound.
+        let approvals_key = ApprovalsKey {
+            approvals_sub_id: sub_id,
+        };
+        let current_ledger = env.ledger().sequence();
+        let mut pending: Vec<u64> = Vec::new(&env);
+        for id in env
+            .storage()
+            .persistent()
+            .get::<ApprovalsKey, Vec<u64>>(&approvals_key)
+            .unwrap_or(Vec::new(&env))
+            .iter()
+        {
+            if id == approval_id {
+                continue;
+            }
+            let id_key = ApprovalKey {
+                sub_id,
+                approval_id: id,
+            };
+            match env
+                .storage()
+                .persistent()
+                .get::<ApprovalKey, RenewalApproval>(&id_key)
+            {
+                Some(a) if !a.used && current_ledger <= a.expires_at => pending.push_back(id),
+                Some(_) => env.storage().persistent().remove(&id_key),
+                None => {}
+            }
+        }
+        if pending.len() >= MAX_PENDING_APPROVALS {
+            return Err(RenewalError::TooManyApprovals);
+        }
+        pending.push_back(approval_id);
+        env.storage().persistent().set(&approvals_key, &pending);
 
         let approval = RenewalApproval {
             sub_id,
@@ -569,69 +1806,96 @@ impl SubscriptionRenewalContract {
             expires_at,
         }
         .publish(&env);
+
+        Self::record_log(
+            &env,
+            sub_id,
+            LogPayload::Approval(ApprovalPayload {
+                approval_id,
+                max_spend,
+                expires_at,
+            }),
+            String::from_str(&env, "Renewal approved"),
+        );
+        Ok(())
     }
 
-    fn consume_approval(env: &Env, sub_id: u64, approval_id: u64, amount: i128) -> bool {
+    fn consume_approval(
+        env: &Env,
+        sub_id: u64,
+        approval_id: u64,
+        amount: i128,
+    ) -> Result<(), RenewalError> {
         let key = ApprovalKey {
             sub_id,
             approval_id,
         };
 
-        let approval_opt: Option<RenewalApproval> = env.storage().persistent().get(&key);
-
-        if approval_opt.is_none() {
-            ApprovalRejected {
-                sub_id,
-                approval_id,
-                reason: 4,
-            }
-            .publish(env);
-            return false;
-        }
-
-        let mut approval = approval_opt.unwrap();
+        let mut approval: RenewalApproval = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(RenewalError::ApprovalNotFound)?;
 
         if approval.used {
-            ApprovalRejected {
-                sub_id,
-                approval_id,
-                reason: 2,
-            }
-            .publish(env);
-            return false;
+            return Err(RenewalError::ApprovalUsed);
         }
-
-        let current_ledger = env.ledger().sequence();
-        if current_ledger > approval.expires_at {
-            ApprovalRejected {
-                sub_id,
-                approval_id,
-                reason: 1,
-            }
-            .publish(env);
-            return false;
+        if env.ledger().sequence() > approval.expires_at {
+            return Err(RenewalError::ApprovalExpired);
         }
-
         if amount > approval.max_spend {
-            ApprovalRejected {
-                sub_id,
-                approval_id,
-                reason: 3,
-            }
-            .publish(env);
-            return false;
+            return Err(RenewalError::ApprovalExceedsMaxSpend);
         }
 
         approval.used = true;
         env.storage().persistent().set(&key, &approval);
-        true
+
+        let approvals_key = ApprovalsKey {
+            approvals_sub_id: sub_id,
+        };
+        if let Some(mut pending) = env
+            .storage()
+            .persistent()
+            .get::<ApprovalsKey, Vec<u64>>(&approvals_key)
+        {
+            if let Some(index) = pending.first_index_of(approval_id) {
+                pending.remove(index);
+                env.storage().persistent().set(&approvals_key, &pending);
+            }
+        }
+        Ok(())
     }
 
     // ── Renewal lock management ────────────────────────────────────
 
-    pub fn acquire_renewal_lock(env: Env, sub_id: u64, lock_timeout: u32) {
+    /// Lock `sub_id` for a renewal by `holder`, who must be the owner, an
+    /// executor allowed to lock, or an allowed agent holding
+    /// `AgentScope::Renewals`.
+    /// Only the holder can renew under the lock.
+    pub fn acquire_renewal_lock(
+        env: Env,
+        holder: Address,
+        sub_id: u64,
+        lock_timeout: u32,
+    ) -> Result<(), RenewalError> {
         if Self::is_paused(env.clone()) {
-            panic!("Protocol is paused");
+            return Err(RenewalError::ProtocolPaused);
+        }
+        if lock_timeout == 0 || lock_timeout > MAX_LOCK_TIMEOUT {
+            return Err(RenewalError::InvalidLockTimeout);
+        }
+
+        let data: SubscriptionData = env
+            .storage()
+            .persistent()
+            .get(&sub_id)
+            .ok_or(RenewalError::SubscriptionNotFound)?;
+
+        holder.require_auth();
+        if !Self::is_permitted(&env, sub_id, &data, &holder, |p| p.can_lock)
+            && !Self::is_agent(&env, sub_id, &holder, AgentScope::Renewals)
+        {
+            return Err(RenewalError::Unauthorized);
         }
 
         let lock_key = RenewalLockKey {
@@ -644,8 +1908,8 @@ impl SubscriptionRenewalContract {
             .persistent()
             .get::<RenewalLockKey, RenewalLockData>(&lock_key)
         {
-            if current_ledger < existing.locked_at + existing.lock_timeout {
-                panic!("Renewal lock active");
+            if !existing.is_expired(current_ledger) {
+                return Err(RenewalError::RenewalLockActive);
             }
             RenewalLockExpired {
                 sub_id,
@@ -656,6 +1920,7 @@ impl SubscriptionRenewalContract {
         }
 
         let lock_data = RenewalLockData {
+            holder: holder.clone(),
             locked_at: current_ledger,
             lock_timeout,
         };
@@ -663,21 +1928,39 @@ impl SubscriptionRenewalContract {
 
         RenewalLockAcquired {
             sub_id,
+            holder,
             locked_at: current_ledger,
             lock_timeout,
         }
         .publish(&env);
+        Ok(())
     }
 
-    pub fn release_renewal_lock(env: Env, sub_id: u64) {
+    /// Release the lock on `sub_id`. Only the holder or the subscription
+    /// owner can release a live lock; once expired, anyone can clear it.
+    pub fn release_renewal_lock(
+        env: Env,
+        caller: Address,
+        sub_id: u64,
+    ) -> Result<(), RenewalError> {
         let lock_key = RenewalLockKey {
             lock_sub_id: sub_id,
         };
-        if !env.storage().persistent().has(&lock_key) {
-            panic!("No renewal lock to release");
-        }
+        let lock_data: RenewalLockData = env
+            .storage()
+            .persistent()
+            .get(&lock_key)
+            .ok_or(RenewalError::NoRenewalLock)?;
 
+        caller.require_auth();
         let current_ledger = env.ledger().sequence();
+        if caller != lock_data.holder && !lock_data.is_expired(current_ledger) {
+            let data = Self::get_sub(env.clone(), sub_id)?;
+            if caller != data.owner {
+                return Err(RenewalError::NotLockHolder);
+            }
+        }
+
         env.storage().persistent().remove(&lock_key);
 
         RenewalLockReleased {
@@ -685,6 +1968,7 @@ impl SubscriptionRenewalContract {
             released_at: current_ledger,
         }
         .publish(&env);
+        Ok(())
     }
 
     pub fn get_renewal_lock(env: Env, sub_id: u64) -> Option<RenewalLockData> {
@@ -696,19 +1980,18 @@ impl SubscriptionRenewalContract {
 
     // ── Renewal logic ─────────────────────────────────────────────
 
+    /// Charge the cycle starting at `next_due`. Rejected before `next_due`
+    /// or outside the renewal window, if one is set. Failed attempts are
+    /// retried according to the subscription's `RetryPolicy`.
     pub fn renew(
         env: Env,
         caller: Address,
         sub_id: u64,
         approval_id: u64,
         amount: i128,
-        max_retries: u32,
-        cooldown_ledgers: u32,
-        cycle_id: u64,
-        succeed: bool,
-    ) -> bool {
+    ) -> Result<bool, RenewalError> {
         if Self::is_paused(env.clone()) {
-            panic!("Protocol is paused");
+            return Err(RenewalError::ProtocolPaused);
         }
 
         let key = sub_id;
@@ -716,21 +1999,32 @@ impl SubscriptionRenewalContract {
             .storage()
             .persistent()
             .get(&key)
-            .expect("Subscription not found");
+            .ok_or(RenewalError::SubscriptionNotFound)?;
 
         caller.require_auth();
-        let executor_key = ExecutorKey { sub_id };
-        let executor: Option<Address> = env.storage().persistent().get(&executor_key);
+        if !Self::is_permitted(&env, sub_id, &data, &caller, |p| p.can_renew)
+            && !Self::is_agent(&env, sub_id, &caller, AgentScope::Renewals)
+        {
+            return Err(RenewalError::Unauthorized);
+        }
 
-        if caller != data.owner && Some(caller.clone()) != executor {
-            panic!("Unauthorized: caller must be owner or executor");
+        if data.state == SubscriptionState::Cancelled {
+            return Err(RenewalError::NotActive);
         }
 
-        if !Self::consume_approval(&env, sub_id, approval_id, amount) {
-            panic!("Invalid or expired approval");
+        Self::consume_approval(&env, sub_id, approval_id, amount)?;
+
+        let cycle_key = CycleKey {
+            cycle_sub_id: sub_id,
+        };
+        let cycle_id = data.next_due;
+        if env.ledger().timestamp() < cycle_id {
+            return Err(RenewalError::NotYetDue);
         }
 
-        let window_key = WindowKey { sub_id };
+        let window_key = WindowKey {
+            window_sub_id: sub_id,
+        };
         if let Some(window) = env
             .storage()
             .persistent()
@@ -738,64 +2032,83 @@ impl SubscriptionRenewalContract {
         {
             let current_time = env.ledger().timestamp();
             if current_time < window.billing_start || current_time > window.billing_end {
-                panic!("Outside renewal window");
+                return Err(RenewalError::OutsideWindow);
             }
         }
 
         if data.state == SubscriptionState::Failed {
-            panic!("Subscription is in FAILED state");
+            return Err(RenewalError::SubscriptionFailed);
+        }
+        if data.state == SubscriptionState::Paused {
+            return Err(RenewalError::SubscriptionPaused);
         }
 
-        let current_ledger = env.ledger().sequence();
+        Self::check_price_change_consent(&env, sub_id)?;
 
-        // 4. Verify renewal lock exists and is not expired
+        // Verify renewal lock exists and is not expired
         let lock_key = RenewalLockKey {
             lock_sub_id: sub_id,
         };
         let lock_data: Option<RenewalLockData> = env.storage().persistent().get(&lock_key);
         let current_ledger = env.ledger().sequence();
         match lock_data {
-            None => panic!("Renewal lock required"),
+            None => return Err(RenewalError::RenewalLockRequired),
             Some(ref ld) => {
-                if current_ledger >= ld.locked_at + ld.lock_timeout {
-                    panic!("Renewal lock expired");
+                if ld.is_expired(current_ledger) {
+                    return Err(RenewalError::RenewalLockExpired);
+                }
+                if ld.holder != caller {
+                    return Err(RenewalError::NotLockHolder);
                 }
             }
         }
 
-        let cycle_key = CycleKey { sub_id };
-        let last_cycle: Option<u64> = env.storage().persistent().get(&cycle_key);
-        if let Some(last) = last_cycle {
-            if cycle_id == last {
-                DuplicateRenewalRejected { sub_id, cycle_id }.publish(&env);
-                panic!("Duplicate renewal for cycle");
-            }
+        if data.failure_count > 0
+            && current_ledger
+                < data
+                    .last_attempt_ledger
+                    .saturating_add(Self::retry_cooldown(&data))
+        {
+            return Err(RenewalError::CooldownActive);
         }
 
-        if data.failure_count > 0 && current_ledger < data.last_attempt_ledger + cooldown_ledgers {
-            panic!("Cooldown period active");
+        if Self::compute_integrity_hash(&env, &data) != data.integrity_hash {
+            return Err(RenewalError::IntegrityViolation);
         }
 
-        let mut integrity_data = soroban_sdk::Vec::<soroban_sdk::Val>::new(&env);
-        integrity_data.push_back(data.merchant.into_val(&env));
-        integrity_data.push_back(data.amount.into_val(&env));
-        integrity_data.push_back(data.frequency.into_val(&env));
-        integrity_data.push_back(data.spending_cap.into_val(&env));
+        Self::apply_pending_plan(&env, sub_id, &mut data);
+
+        if amount != Self::due_amount(&data) {
+            return Err(RenewalError::AmountMismatch);
+        }
 
-        let current_hash = env.crypto().sha256(&integrity_data.to_xdr(&env));
-        let current_hash_bytes: soroban_sdk::BytesN<32> = current_hash.into();
+        if data.total_charged + amount > data.spending_cap {
+            SpendingCapExceeded {
+                sub_id,
+                total_charged: data.total_charged,
+                amount,
+                spending_cap: data.spending_cap,
+            }
+            .publish(&env);
+            env.storage().persistent().set(&key, &data);
+
+            env.storage().persistent().remove(&lock_key);
+            RenewalLockReleased {
+                sub_id,
+                released_at: current_ledger,
+            }
+            .publish(&env);
 
-        if current_hash_bytes.as_ref() != data.integrity_hash.as_ref() {
-            IntegrityViolation { sub_id }.publish(&env);
-            panic!("Subscription integrity violation: parameters tampered");
+            return Ok(false);
         }
 
-        if succeed {
+        if Self::settle(&env, &data, amount) {
             let previous_state = data.state;
 
             data.state = SubscriptionState::Active;
             data.failure_count = 0;
             data.last_attempt_ledger = current_ledger;
+            Self::record_charge(&mut data, amount, cycle_id);
             env.storage().persistent().set(&key, &data);
 
             env.storage().persistent().set(&cycle_key, &cycle_id);
@@ -813,7 +2126,7 @@ impl SubscriptionRenewalContract {
                 .storage()
                 .persistent()
                 .get(&lc_key)
-                .expect("Lifecycle data not found");
+                .ok_or(RenewalError::LifecycleNotFound)?;
             let now = env.ledger().timestamp();
             lifecycle.last_renewed_at = now;
 
@@ -824,7 +2137,7 @@ impl SubscriptionRenewalContract {
             }
             .publish(&env);
 
-            if previous_state == SubscriptionState::Retrying {
+            if previous_state != SubscriptionState::Active {
                 lifecycle.activated_at = now;
                 LifecycleTimestampUpdated {
                     sub_id,
@@ -835,6 +2148,14 @@ impl SubscriptionRenewalContract {
             }
             env.storage().persistent().set(&lc_key, &lifecycle);
 
+            if previous_state != SubscriptionState::Active {
+                StateTransition {
+                    sub_id,
+                    new_state: SubscriptionState::Active,
+                }
+                .publish(&env);
+            }
+
             env.storage().persistent().remove(&lock_key);
             RenewalLockReleased {
                 sub_id,
@@ -845,11 +2166,15 @@ impl SubscriptionRenewalContract {
             Self::record_log(
                 &env,
                 sub_id,
-                2,
-                soroban_sdk::String::from_str(&env, "Renewal successful"),
+                LogPayload::Renewal(RenewalPayload {
+                    amount,
+                    cycle_id,
+                    ledger: current_ledger,
+                }),
+                String::from_str(&env, "Renewal successful"),
             );
 
-            true
+            Ok(true)
         } else {
             data.failure_count += 1;
             data.last_attempt_ledger = current_ledger;
@@ -861,33 +2186,49 @@ impl SubscriptionRenewalContract {
             }
             .publish(&env);
 
-            if data.failure_count > max_retries {
-                data.state = SubscriptionState::Failed;
-                StateTransition {
+            let grace_ends_at = cycle_id.saturating_add(data.retry_policy.grace_period);
+            let new_state = if env.ledger().timestamp() < grace_ends_at {
+                SubscriptionState::Grace
+            } else if data.failure_count > data.retry_policy.max_retries {
+                SubscriptionState::Failed
+            } else {
+                SubscriptionState::Retrying
+            };
+
+            if new_state == SubscriptionState::Grace && data.state != SubscriptionState::Grace {
+                GracePeriodStarted {
                     sub_id,
-                    new_state: SubscriptionState::Failed,
+                    ends_at: grace_ends_at,
                 }
                 .publish(&env);
+            } else if data.state == SubscriptionState::Grace
+                && new_state != SubscriptionState::Grace
+            {
+                GracePeriodEnded { sub_id }.publish(&env);
+            }
+            data.state = new_state;
+            StateTransition { sub_id, new_state }.publish(&env);
 
+            if new_state == SubscriptionState::Failed {
                 Self::record_log(
                     &env,
                     sub_id,
-                    3,
-                    soroban_sdk::String::from_str(&env, "Renewal failed - max retries exceeded"),
+                    LogPayload::Failure(FailurePayload {
+                        reason: FAILURE_REASON_PAYMENT_REJECTED,
+                        failure_count: data.failure_count,
+                    }),
+                    String::from_str(&env, "Renewal failed - max retries exceeded"),
                 );
             } else {
-                data.state = SubscriptionState::Retrying;
-                StateTransition {
-                    sub_id,
-                    new_state: SubscriptionState::Retrying,
-                }
-                .publish(&env);
-
                 Self::record_log(
                     &env,
                     sub_id,
-                    4,
-                    soroban_sdk::String::from_str(&env, "Renewal failed - scheduled for retry"),
+                    LogPayload::Retry(RetryPayload {
+                        failure_count: data.failure_count,
+                        retry_after_ledger: current_ledger
+                            .saturating_add(Self::retry_cooldown(&data)),
+                    }),
+                    String::from_str(&env, "Renewal failed - scheduled for retry"),
                 );
             }
 
@@ -900,26 +2241,165 @@ impl SubscriptionRenewalContract {
             }
             .publish(&env);
 
-            false
+            Ok(false)
         }
     }
 
     // ── Internal helpers ──────────────────────────────────────────
 
-    fn record_log(env: &Env, sub_id: u64, event_type: u32, data_str: soroban_sdk::String) {
-        if let Some(_log_addr) = env
+    /// Switch `data` to the plan scheduled with `PlanChangeEffect::NextRenewal`,
+    /// if any. The caller persists `data`.
+    fn apply_pending_plan(env: &Env, sub_id: u64, data: &mut SubscriptionData) {
+        let plan_key = PlanChangeKey {
+            plan_sub_id: sub_id,
+        };
+        if let Some(plan) = env
+            .storage()
+            .persistent()
+            .get::<PlanChangeKey, PendingPlan>(&plan_key)
+        {
+            data.amount = plan.amount;
+            data.frequency = plan.frequency.clone();
+            data.integrity_hash = Self::compute_integrity_hash(env, data);
+            env.storage().persistent().remove(&plan_key);
+
+            PlanChanged {
+                sub_id,
+                amount: plan.amount,
+                frequency: plan.frequency,
+                proration: 0,
+            }
+            .publish(env);
+        }
+    }
+
+    /// Reject charges while a price change proposal is past its deadline
+    /// without an answer; `expire_price_change` resolves it.
+    fn check_price_change_consent(env: &Env, sub_id: u64) -> Result<(), RenewalError> {
+        let price_key = PriceChangeKey {
+            price_sub_id: sub_id,
+        };
+        if let Some(proposal) = env
+            .storage()
+            .persistent()
+            .get::<PriceChangeKey, PriceChangeProposal>(&price_key)
+        {
+            if env.ledger().timestamp() > proposal.deadline {
+                return Err(RenewalError::PriceChangeConsentExpired);
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the owner of `sub_id` allowed `addr` and the configured agent
+    /// registry grants it the `scope`. A failing registry grants nothing.
+    fn is_agent(env: &Env, sub_id: u64, addr: &Address, scope: AgentScope) -> bool {
+        if !Self::get_allowed_agents(env.clone(), sub_id).contains(addr) {
+            return false;
+        }
+        match env
             .storage()
             .instance()
-            .get::<_, Address>(&ContractKey::LoggingContract)
+            .get::<_, Address>(&ContractKey::AgentRegistry)
         {
-            env.events().publish(
-                (soroban_sdk::symbol_short!("log"), sub_id),
-                (event_type, data_str),
-            );
+            Some(registry) => matches!(
+                AgentRegistryClient::new(env, &registry).try_has_scope(addr, &scope),
+                Ok(Ok(true))
+            ),
+            None => false,
         }
     }
+
+    /// Whether `addr` is the owner of `sub_id` or an unexpired executor
+    /// whose permissions satisfy `allowed`.
+    fn is_permitted(
+        env: &Env,
+        sub_id: u64,
+        data: &SubscriptionData,
+        addr: &Address,
+        allowed: fn(&ExecutorPermissions) -> bool,
+    ) -> bool {
+        if *addr == data.owner {
+            return true;
+        }
+        match Self::get_executor(env.clone(), sub_id, addr.clone()) {
+            Some(p) => {
+                allowed(&p) && (p.expires_at == 0 || env.ledger().timestamp() < p.expires_at)
+            }
+            None => false,
+        }
+    }
+
+    /// Hash of the billing terms that must not change after creation.
+    fn compute_integrity_hash(env: &Env, data: &SubscriptionData) -> BytesN<32> {
+        let mut integrity_data = soroban_sdk::Vec::<soroban_sdk::Val>::new(env);
+        integrity_data.push_back(data.merchant.into_val(env));
+        integrity_data.push_back(data.token.into_val(env));
+        integrity_data.push_back(data.amount.into_val(env));
+        integrity_data.push_back(data.frequency.into_val(env));
+        integrity_data.push_back(data.spending_cap.into_val(env));
+        integrity_data.push_back(data.trial.into_val(env));
+        integrity_data.push_back(data.retry_policy.into_val(env));
+
+        env.crypto().sha256(&integrity_data.to_xdr(env)).into()
+    }
+
+    /// Ledgers to wait before retrying after the current `failure_count`
+    /// failed attempts.
+    fn retry_cooldown(data: &SubscriptionData) -> u32 {
+        let policy = &data.retry_policy;
+        let backoff = policy
+            .backoff_factor
+            .saturating_pow(data.failure_count.saturating_sub(1));
+        policy.cooldown_ledgers.saturating_mul(backoff)
+    }
+
+    /// Book a successful charge of `amount` for the cycle starting at
+    /// `cycle_id` and move `next_due` to the following cycle.
+    fn record_charge(data: &mut SubscriptionData, amount: i128, cycle_id: u64) {
+        let price = Self::scheduled_amount(data);
+        data.total_charged += amount;
+        data.cycles_charged += 1;
+        data.credit -= price - amount;
+        data.current_price = price;
+        data.next_due = data.frequency.next_due(cycle_id);
+    }
+
+    /// Introductory price for the first `intro_cycles` charges, then `amount`.
+    fn scheduled_amount(data: &SubscriptionData) -> i128 {
+        if data.cycles_charged < data.trial.intro_cycles {
+            data.trial.intro_amount
+        } else {
+            data.amount
+        }
+    }
+
+    /// Scheduled price less any outstanding proration credit.
+    fn due_amount(data: &SubscriptionData) -> i128 {
+        (Self::scheduled_amount(data) - data.credit).max(0)
+    }
+
+    /// Pull `amount` from the owner to the merchant using the allowance the
+    /// owner granted this contract on the subscription's token. Returns
+    /// `false` if the transfer was rejected (insufficient allowance or balance).
+    fn settle(env: &Env, data: &SubscriptionData, amount: i128) -> bool {
+        if amount == 0 {
+            // Fully covered by credit.
+            return true;
+        }
+        let client = token::Client::new(env, &data.token);
+
+        matches!(
+            client.try_transfer_from(
+                &env.current_contract_address(),
+                &data.owner,
+                &data.merchant,
+                &amount,
+            ),
+            Ok(Ok(()))
+        )
+    }
 }
 
 #[cfg(test)]
 mod test;
-
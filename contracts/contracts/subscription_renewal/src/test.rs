@@ -1,87 +1,2002 @@
-use soroban_sdk::{
-    contract, contractimpl, contracttype, contractevent,
-    Address, Env, Symbol,
-};
+#![cfg(test)]
 
-#[contracttype]
-#[derive(Clone)]
-pub struct Subscription {
-    pub subscriber: Address,
-    pub plan_id: Symbol,
-    pub next_payment_time: u64,
-    pub active: bool,
+use super::*;
+use soroban_sdk::testutils::{Address as _, Ledger};
+use soroban_sdk::token::{StellarAssetClient, TokenClient};
+use soroban_sdk::Env;
+
+struct Setup<'a> {
+    env: Env,
+    client: SubscriptionRenewalContractClient<'a>,
+    token: TokenClient<'a>,
+    owner: Address,
+    merchant: Address,
+}
+
+/// Id assigned to the subscription created by `setup`.
+const SUB_ID: u64 = 1;
+const AMOUNT: i128 = 100;
+
+fn retry_policy(max_retries: u32, cooldown_ledgers: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_retries,
+        cooldown_ledgers,
+        backoff_factor: 1,
+        grace_period: 0,
+    }
+}
+
+fn setup<'a>() -> Setup<'a> {
+    setup_with_policy(retry_policy(3, 0))
+}
+
+/// Create `SUB_ID` with `policy`; other subscriptions get three retries and
+/// no cooldown by default.
+fn setup_with_policy<'a>(policy: RetryPolicy) -> Setup<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+
+    let contract_id = env.register(SubscriptionRenewalContract, ());
+    let client = SubscriptionRenewalContractClient::new(&env, &contract_id);
+
+    let admin = Address::generate(&env);
+    let owner = Address::generate(&env);
+    let merchant = Address::generate(&env);
+
+    let sac = env.register_stellar_asset_contract_v2(admin.clone());
+    let token = TokenClient::new(&env, &sac.address());
+    StellarAssetClient::new(&env, &sac.address()).mint(&owner, &10_000);
+    token.approve(&owner, &contract_id, &10_000, &1_000);
+
+    client.init(&admin);
+    client.set_default_retry_policy(&retry_policy(3, 0));
+    client.init_sub(
+        &owner,
+        &merchant,
+        &sac.address(),
+        &AMOUNT,
+        &BillingInterval::Seconds(2_592_000),
+        &10_000,
+        &None,
+        &Some(policy),
+    );
+
+    Setup {
+        env,
+        client,
+        token,
+        owner,
+        merchant,
+    }
+}
+
+/// Move ledger time to the start of `sub_id`'s next billing cycle.
+fn advance_to_next_due(s: &Setup, sub_id: u64) {
+    let next_due = s.client.get_sub(&sub_id).next_due;
+    s.env.ledger().with_mut(|li| li.timestamp = next_due);
+}
+
+/// Permissions for an executor that may renew and lock, but not manage the
+/// window, until `expires_at` (0 never expires).
+fn executor_permissions(expires_at: u64) -> ExecutorPermissions {
+    ExecutorPermissions {
+        can_renew: true,
+        can_lock: true,
+        can_manage_window: false,
+        expires_at,
+    }
+}
+
+/// Approve and lock a single renewal attempt for `SUB_ID`.
+fn prepare_renewal(s: &Setup, approval_id: u64) {
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &approval_id, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+}
+
+#[test]
+fn test_renewal_success() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+
+    assert!(s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Active);
+    assert_eq!(data.failure_count, 0);
+    assert_eq!(s.token.balance(&s.owner), 10_000 - AMOUNT);
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+    assert_eq!(s.client.get_renewal_lock(&SUB_ID), None);
+}
+
+#[test]
+fn test_renewal_consumes_allowance() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    let allowance = s.token.allowance(&s.owner, &s.client.address);
+    assert_eq!(allowance, 10_000 - AMOUNT);
+}
+
+#[test]
+fn test_renewal_fails_without_allowance() {
+    let s = setup();
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    prepare_renewal(&s, 1);
+
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Retrying);
+    assert_eq!(data.failure_count, 1);
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert_eq!(s.client.get_renewal_lock(&SUB_ID), None);
+}
+
+#[test]
+fn test_renewal_fails_with_insufficient_balance() {
+    let s = setup();
+    s.token
+        .transfer(&s.owner, &s.merchant, &(10_000 - AMOUNT + 1));
+    prepare_renewal(&s, 1);
+
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).failure_count, 1);
+    assert_eq!(s.token.balance(&s.owner), AMOUNT - 1);
 }
 
-#[contracttype]
-#[derive(Clone)]
-pub enum DataKey {
-    Subscription(Address),
+#[test]
+fn test_multiple_failures_then_success() {
+    let s = setup();
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+
+    prepare_renewal(&s, 1);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+    prepare_renewal(&s, 2);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).failure_count, 2);
+
+    s.token
+        .approve(&s.owner, &s.client.address, &10_000, &1_000);
+    s.env.ledger().with_mut(|li| li.timestamp += 60);
+    prepare_renewal(&s, 3);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &3, &AMOUNT));
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Active);
+    assert_eq!(data.failure_count, 0);
+
+    let lifecycle = s.client.get_lifecycle(&SUB_ID);
+    assert_eq!(lifecycle.activated_at, s.env.ledger().timestamp());
+    assert_eq!(lifecycle.last_renewed_at, s.env.ledger().timestamp());
 }
 
-#[contractevent]
-pub struct SubscriptionCreated {
-    pub subscriber: Address,
-    pub plan_id: Symbol,
+#[test]
+fn test_max_retries_exceeded() {
+    let s = setup_with_policy(retry_policy(1, 0));
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Retrying);
+
+    prepare_renewal(&s, 2);
+    s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Failed);
 }
 
-#[contractevent]
-pub struct SubscriptionRenewed {
-    pub subscriber: Address,
+#[test]
+fn test_cannot_renew_failed_subscription() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Failed);
+
+    prepare_renewal(&s, 2);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &2, &AMOUNT),
+        Err(Ok(RenewalError::SubscriptionFailed))
+    );
 }
 
-#[contract]
-pub struct SubscriptionRenewal;
+#[test]
+fn test_lifecycle_not_updated_on_renewal_failure() {
+    let s = setup();
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    prepare_renewal(&s, 1);
 
-#[contractimpl]
-impl SubscriptionRenewal {
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    assert_eq!(s.client.get_lifecycle(&SUB_ID).last_renewed_at, 0);
+}
+
+#[test]
+fn test_cooldown_enforcement() {
+    let s = setup_with_policy(retry_policy(3, 10));
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
 
-    pub fn create_subscription(
-        env: Env,
-        subscriber: Address,
-        plan_id: Symbol,
-        next_payment_time: u64,
-    ) {
-        subscriber.require_auth();
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
 
-        let subscription = Subscription {
-            subscriber: subscriber.clone(),
-            plan_id: plan_id.clone(),
-            next_payment_time,
-            active: true,
-        };
+    prepare_renewal(&s, 2);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &2, &AMOUNT),
+        Err(Ok(RenewalError::CooldownActive))
+    );
+}
 
-        env.storage()
-            .instance()
-            .set(&DataKey::Subscription(subscriber.clone()), &subscription);
+#[test]
+fn test_next_due_starts_at_creation() {
+    let s = setup();
+    assert_eq!(
+        s.client.get_sub(&SUB_ID).next_due,
+        s.env.ledger().timestamp()
+    );
+}
+
+#[test]
+fn test_success_advances_next_due_by_frequency() {
+    let s = setup();
+    let due = s.client.get_sub(&SUB_ID).next_due;
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).next_due, due + 2_592_000);
+}
+
+#[test]
+fn test_failure_keeps_next_due() {
+    let s = setup();
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    let due = s.client.get_sub(&SUB_ID).next_due;
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).next_due, due);
+}
+
+#[test]
+fn test_monthly_frequency_follows_calendar() {
+    let s = setup();
+    let jan_31 = billing_calendar::days_from_civil(2025, 1, 31) * 86_400;
+    s.env.ledger().with_mut(|li| li.timestamp = jan_31);
+
+    let sub_id = s.client.init_sub(
+        &s.owner,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &BillingInterval::Monthly(31),
+        &10_000,
+        &None,
+        &None,
+    );
+    s.client
+        .approve_renewal(&s.owner, &sub_id, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &sub_id, &10);
+    s.client.renew(&s.owner, &sub_id, &1, &AMOUNT);
+
+    let feb_28 = billing_calendar::days_from_civil(2025, 2, 28) * 86_400;
+    assert_eq!(s.client.get_sub(&sub_id).next_due, feb_28);
+}
+
+#[test]
+fn test_init_sub_rejects_invalid_frequency() {
+    let s = setup();
+    assert_eq!(
+        s.client.try_init_sub(
+            &s.owner,
+            &s.merchant,
+            &s.token.address,
+            &AMOUNT,
+            &BillingInterval::Monthly(0),
+            &10_000,
+            &None,
+            &None,
+        ),
+        Err(Ok(RenewalError::InvalidFrequency))
+    );
+}
+
+const TRIAL_SUB_ID: u64 = 2;
+const TRIAL_PERIOD: u64 = 7 * 86_400;
+const INTRO_AMOUNT: i128 = 50;
+
+/// Create `TRIAL_SUB_ID` with a week-long trial and two intro-priced cycles.
+fn init_trial_sub(s: &Setup) {
+    let sub_id = s.client.init_sub(
+        &s.owner,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &BillingInterval::Seconds(2_592_000),
+        &10_000,
+        &Some(TrialTerms {
+            trial_period: TRIAL_PERIOD,
+            intro_amount: INTRO_AMOUNT,
+            intro_cycles: 2,
+        }),
+        &None,
+    );
+    assert_eq!(sub_id, TRIAL_SUB_ID);
+}
+
+#[test]
+fn test_init_sub_rejects_overflowing_trial_or_interval() {
+    let s = setup();
+    s.env.ledger().with_mut(|li| li.timestamp = 1_000);
+    assert_eq!(
+        s.client.try_init_sub(
+            &s.owner,
+            &s.merchant,
+            &s.token.address,
+            &AMOUNT,
+            &BillingInterval::Seconds(2_592_000),
+            &10_000,
+            &Some(TrialTerms {
+                trial_period: u64::MAX,
+                intro_amount: INTRO_AMOUNT,
+                intro_cycles: 2,
+            }),
+            &None,
+        ),
+        Err(Ok(RenewalError::InvalidTrialTerms))
+    );
+    assert_eq!(
+        s.client.try_init_sub(
+            &s.owner,
+            &s.merchant,
+            &s.token.address,
+            &AMOUNT,
+            &BillingInterval::Seconds(u64::MAX),
+            &10_000,
+            &None,
+            &None,
+        ),
+        Err(Ok(RenewalError::InvalidFrequency))
+    );
+}
+
+fn prepare_trial_renewal(s: &Setup, approval_id: u64, amount: i128) {
+    s.client
+        .approve_renewal(&s.owner, &TRIAL_SUB_ID, &approval_id, &amount, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &TRIAL_SUB_ID, &10);
+}
+
+fn renew_trial_sub(s: &Setup, approval_id: u64, amount: i128) -> bool {
+    prepare_trial_renewal(s, approval_id, amount);
+    s.client
+        .renew(&s.owner, &TRIAL_SUB_ID, &approval_id, &amount)
+}
+
+#[test]
+fn test_trial_defers_first_charge() {
+    let s = setup();
+    let created_at = s.env.ledger().timestamp();
+    init_trial_sub(&s);
+
+    let data = s.client.get_sub(&TRIAL_SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Trialing);
+    assert_eq!(data.next_due, created_at + TRIAL_PERIOD);
+    assert_eq!(s.client.get_lifecycle(&TRIAL_SUB_ID).activated_at, 0);
+}
+
+#[test]
+fn test_renewal_rejected_during_trial() {
+    let s = setup();
+    init_trial_sub(&s);
+    prepare_trial_renewal(&s, 1, INTRO_AMOUNT);
+    assert_eq!(
+        s.client
+            .try_renew(&s.owner, &TRIAL_SUB_ID, &1, &INTRO_AMOUNT),
+        Err(Ok(RenewalError::NotYetDue))
+    );
+}
+
+#[test]
+fn test_trial_converts_through_intro_to_regular_price() {
+    let s = setup();
+    init_trial_sub(&s);
+
+    advance_to_next_due(&s, TRIAL_SUB_ID);
+    assert_eq!(s.client.get_due_amount(&TRIAL_SUB_ID), INTRO_AMOUNT);
+    assert!(renew_trial_sub(&s, 1, INTRO_AMOUNT));
+
+    let data = s.client.get_sub(&TRIAL_SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Active);
+    assert_eq!(data.cycles_charged, 1);
+    assert_eq!(
+        s.client.get_lifecycle(&TRIAL_SUB_ID).activated_at,
+        s.env.ledger().timestamp()
+    );
+
+    advance_to_next_due(&s, TRIAL_SUB_ID);
+    assert!(renew_trial_sub(&s, 2, INTRO_AMOUNT));
+
+    advance_to_next_due(&s, TRIAL_SUB_ID);
+    assert_eq!(s.client.get_due_amount(&TRIAL_SUB_ID), AMOUNT);
+    assert!(renew_trial_sub(&s, 3, AMOUNT));
+
+    assert_eq!(s.token.balance(&s.merchant), INTRO_AMOUNT * 2 + AMOUNT);
+}
+
+#[test]
+fn test_regular_amount_rejected_during_intro() {
+    let s = setup();
+    init_trial_sub(&s);
+    advance_to_next_due(&s, TRIAL_SUB_ID);
+    prepare_trial_renewal(&s, 1, AMOUNT);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &TRIAL_SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::AmountMismatch))
+    );
+}
+
+#[test]
+fn test_integrity_violation_on_trial_terms_change() {
+    let s = setup();
+    init_trial_sub(&s);
+    s.env.as_contract(&s.client.address, || {
+        let mut data: SubscriptionData = s.env.storage().persistent().get(&TRIAL_SUB_ID).unwrap();
+        data.trial.intro_amount = 1;
+        data.trial.intro_cycles = 12;
+        s.env.storage().persistent().set(&TRIAL_SUB_ID, &data);
+    });
+
+    advance_to_next_due(&s, TRIAL_SUB_ID);
+    prepare_trial_renewal(&s, 1, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &TRIAL_SUB_ID, &1, &1),
+        Err(Ok(RenewalError::IntegrityViolation))
+    );
+}
+
+#[test]
+fn test_renewal_rejected_before_next_due() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 2_592_000 - 1);
+    prepare_renewal(&s, 2);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &2, &AMOUNT),
+        Err(Ok(RenewalError::NotYetDue))
+    );
+}
+
+#[test]
+fn test_next_cycle_allowed_once_due() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+
+    advance_to_next_due(&s, SUB_ID);
+    prepare_renewal(&s, 2);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT));
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT * 2);
+}
+
+#[test]
+fn test_due_renewal_still_respects_window() {
+    let s = setup();
+    s.client
+        .set_window(&s.owner, &SUB_ID, &5_000_000, &6_000_000);
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::OutsideWindow))
+    );
+}
 
-        SubscriptionCreated { subscriber, plan_id }.publish(&env);
+#[test]
+fn test_renewal_rejected_without_approval() {
+    let s = setup();
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::ApprovalNotFound))
+    );
+}
+
+#[test]
+fn test_expired_approval_rejected() {
+    let s = setup();
+    s.client.approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &5);
+    s.env.ledger().with_mut(|li| li.sequence_number = 6);
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::ApprovalExpired))
+    );
+}
+
+#[test]
+fn test_used_approval_rejected() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+
+    advance_to_next_due(&s, SUB_ID);
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::ApprovalUsed))
+    );
+}
+
+#[test]
+fn test_amount_exceeds_max_spend() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &(AMOUNT + 1)),
+        Err(Ok(RenewalError::ApprovalExceedsMaxSpend))
+    );
+}
+
+#[test]
+fn test_renew_without_lock_rejected() {
+    let s = setup();
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::RenewalLockRequired))
+    );
+}
+
+#[test]
+fn test_lock_prevents_concurrent_acquisition() {
+    let s = setup();
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&s.owner, &SUB_ID, &10),
+        Err(Ok(RenewalError::RenewalLockActive))
+    );
+}
+
+#[test]
+fn test_stranger_cannot_acquire_lock() {
+    let s = setup();
+    let stranger = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&stranger, &SUB_ID, &10),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_lock_records_holder() {
+    let s = setup();
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert_eq!(s.client.get_renewal_lock(&SUB_ID).unwrap().holder, s.owner);
+}
+
+#[test]
+fn test_only_holder_releases_live_lock() {
+    let s = setup();
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    let stranger = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_release_renewal_lock(&stranger, &SUB_ID),
+        Err(Ok(RenewalError::NotLockHolder))
+    );
+
+    s.client.release_renewal_lock(&s.owner, &SUB_ID);
+    assert_eq!(s.client.get_renewal_lock(&SUB_ID), None);
+}
+
+#[test]
+fn test_lock_timeout_is_bounded() {
+    let s = setup();
+    for timeout in [0, MAX_LOCK_TIMEOUT + 1, u32::MAX] {
+        assert_eq!(
+            s.client
+                .try_acquire_renewal_lock(&s.owner, &SUB_ID, &timeout),
+            Err(Ok(RenewalError::InvalidLockTimeout))
+        );
+    }
+    s.client
+        .acquire_renewal_lock(&s.owner, &SUB_ID, &MAX_LOCK_TIMEOUT);
+}
+
+#[test]
+fn test_owner_releases_removed_executors_lock() {
+    let s = setup();
+    let executor = Address::generate(&s.env);
+    s.client
+        .set_executor(&SUB_ID, &executor, &executor_permissions(0));
+    s.client.acquire_renewal_lock(&executor, &SUB_ID, &10);
+    s.client.remove_executor(&SUB_ID, &executor);
+
+    s.client.release_renewal_lock(&s.owner, &SUB_ID);
+    assert_eq!(s.client.get_renewal_lock(&SUB_ID), None);
+}
+
+#[test]
+fn test_expired_lock_released_by_anyone() {
+    let s = setup();
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    s.env.ledger().with_mut(|li| li.sequence_number += 10);
+
+    let stranger = Address::generate(&s.env);
+    s.client.release_renewal_lock(&stranger, &SUB_ID);
+    assert_eq!(s.client.get_renewal_lock(&SUB_ID), None);
+}
+
+#[test]
+fn test_renew_requires_lock_holder() {
+    let s = setup();
+    let executor = Address::generate(&s.env);
+    s.client
+        .set_executor(&SUB_ID, &executor, &executor_permissions(0));
+    prepare_renewal(&s, 1);
+
+    assert_eq!(
+        s.client.try_renew(&executor, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::NotLockHolder))
+    );
+}
+
+#[test]
+fn test_renew_rejects_stranger() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    let stranger = Address::generate(&s.env);
+    assert_eq!(
+        s.client.try_renew(&stranger, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_executor_can_renew() {
+    let s = setup();
+    let executor = Address::generate(&s.env);
+    s.client
+        .set_executor(&SUB_ID, &executor, &executor_permissions(0));
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&executor, &SUB_ID, &10);
+
+    assert!(s.client.renew(&executor, &SUB_ID, &1, &AMOUNT));
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+}
+
+#[test]
+fn test_multiple_executors_can_renew() {
+    let s = setup();
+    let bot = Address::generate(&s.env);
+    let family = Address::generate(&s.env);
+    s.client
+        .set_executor(&SUB_ID, &bot, &executor_permissions(0));
+    s.client
+        .set_executor(&SUB_ID, &family, &executor_permissions(0));
+    assert_eq!(s.client.get_executors(&SUB_ID).len(), 2);
+
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&bot, &SUB_ID, &10);
+    assert!(s.client.renew(&bot, &SUB_ID, &1, &AMOUNT));
+
+    advance_to_next_due(&s, SUB_ID);
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &2, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&family, &SUB_ID, &10);
+    assert!(s.client.renew(&family, &SUB_ID, &2, &AMOUNT));
+}
+
+#[test]
+fn test_executor_limited_to_granted_permissions() {
+    let s = setup();
+    let executor = Address::generate(&s.env);
+    let permissions = ExecutorPermissions {
+        can_lock: false,
+        ..executor_permissions(0)
+    };
+    s.client.set_executor(&SUB_ID, &executor, &permissions);
+
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&executor, &SUB_ID, &10),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+    assert_eq!(
+        s.client.try_set_window(&executor, &SUB_ID, &100, &200),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+
+    let permissions = ExecutorPermissions {
+        can_manage_window: true,
+        ..permissions
+    };
+    s.client.set_executor(&SUB_ID, &executor, &permissions);
+    s.client.set_window(&executor, &SUB_ID, &100, &200);
+    assert!(s.client.get_window(&SUB_ID).is_some());
+}
+
+#[test]
+fn test_expired_executor_rejected() {
+    let s = setup();
+    let executor = Address::generate(&s.env);
+    let expires_at = s.env.ledger().timestamp() + 100;
+    s.client
+        .set_executor(&SUB_ID, &executor, &executor_permissions(expires_at));
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&executor, &SUB_ID, &10);
+
+    s.env.ledger().with_mut(|li| li.timestamp = expires_at);
+    assert_eq!(
+        s.client.try_renew(&executor, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_removed_executor_rejected() {
+    let s = setup();
+    let executor = Address::generate(&s.env);
+    s.client
+        .set_executor(&SUB_ID, &executor, &executor_permissions(0));
+    s.client.remove_executor(&SUB_ID, &executor);
+    assert_eq!(s.client.get_executor(&SUB_ID, &executor), None);
+
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&executor, &SUB_ID, &10),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+    assert_eq!(
+        s.client.try_remove_executor(&SUB_ID, &executor),
+        Err(Ok(RenewalError::ExecutorNotFound))
+    );
+}
+
+#[test]
+fn test_executor_set_is_bounded() {
+    let s = setup();
+    for _ in 0..MAX_EXECUTORS {
+        let executor = Address::generate(&s.env);
+        s.client
+            .set_executor(&SUB_ID, &executor, &executor_permissions(0));
     }
+    let extra = Address::generate(&s.env);
+    assert_eq!(
+        s.client
+            .try_set_executor(&SUB_ID, &extra, &executor_permissions(0)),
+        Err(Ok(RenewalError::TooManyExecutors))
+    );
+}
+
+#[test]
+fn test_renew_blocked_when_paused() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    s.client.set_paused(&true);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::ProtocolPaused))
+    );
+}
+
+#[test]
+fn test_cancel_sub() {
+    let s = setup();
+    s.client.cancel_sub(&SUB_ID);
+
+    assert_eq!(
+        s.client.get_sub(&SUB_ID).state,
+        SubscriptionState::Cancelled
+    );
+    assert_eq!(
+        s.client.get_lifecycle(&SUB_ID).canceled_at,
+        s.env.ledger().timestamp()
+    );
+}
+
+#[test]
+fn test_cancelled_subscription_cannot_be_charged() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    s.client.cancel_sub(&SUB_ID);
+
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::NotActive))
+    );
+    assert_eq!(
+        s.client
+            .try_approve_renewal(&s.owner, &SUB_ID, &2, &AMOUNT, &1_000),
+        Err(Ok(RenewalError::NotActive))
+    );
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert_eq!(
+        s.client.get_sub(&SUB_ID).state,
+        SubscriptionState::Cancelled
+    );
+}
 
-    pub fn renew_subscription(env: Env, subscriber: Address) {
-        subscriber.require_auth();
+#[test]
+fn test_renew_rejects_amount_other_than_due() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &(AMOUNT - 1)),
+        Err(Ok(RenewalError::AmountMismatch))
+    );
+}
 
-        let key = DataKey::Subscription(subscriber.clone());
-        let mut subscription: Subscription = env
-            .storage()
-            .instance()
-            .get(&key)
-            .unwrap();
+#[test]
+fn test_cancel_clears_outstanding_approvals() {
+    let s = setup();
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &2, &AMOUNT, &1_000);
+    s.client.cancel_sub(&SUB_ID);
 
-        if !subscription.active {
-            panic!("Subscription not active");
+    s.env.as_contract(&s.client.address, || {
+        for approval_id in [1, 2] {
+            assert!(!s.env.storage().persistent().has(&ApprovalKey {
+                sub_id: SUB_ID,
+                approval_id,
+            }));
         }
+    });
+}
+
+#[test]
+fn test_pending_approvals_are_bounded() {
+    let s = setup();
+    for approval_id in 0..MAX_PENDING_APPROVALS as u64 {
+        s.client
+            .approve_renewal(&s.owner, &SUB_ID, &approval_id, &AMOUNT, &1_000);
+    }
+    let extra = MAX_PENDING_APPROVALS as u64;
+    assert_eq!(
+        s.client
+            .try_approve_renewal(&s.owner, &SUB_ID, &extra, &AMOUNT, &1_000),
+        Err(Ok(RenewalError::TooManyApprovals))
+    );
+
+    // Consuming one frees a slot.
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &0, &AMOUNT));
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &extra, &AMOUNT, &1_000);
+}
+
+#[test]
+fn test_renew_outside_window() {
+    let s = setup();
+    s.client.set_window(&s.owner, &SUB_ID, &100, &200);
+    s.env.ledger().with_mut(|li| li.timestamp = 300);
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::OutsideWindow))
+    );
+}
 
-        subscription.next_payment_time += 30 * 24 * 60 * 60;
+#[test]
+fn test_get_sub_exposes_token() {
+    let s = setup();
+    assert_eq!(s.client.get_sub(&SUB_ID).token, s.token.address);
+}
+
+#[test]
+fn test_integrity_violation_on_token_swap() {
+    let s = setup();
+    let other_token = s
+        .env
+        .register_stellar_asset_contract_v2(Address::generate(&s.env))
+        .address();
+    s.env.as_contract(&s.client.address, || {
+        let mut data: SubscriptionData = s.env.storage().persistent().get(&SUB_ID).unwrap();
+        data.token = other_token;
+        s.env.storage().persistent().set(&SUB_ID, &data);
+    });
 
-        env.storage().instance().set(&key, &subscription);
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::IntegrityViolation))
+    );
+}
 
-        SubscriptionRenewed { subscriber }.publish(&env);
+#[test]
+fn test_total_charged_tracks_successful_renewals() {
+    let s = setup();
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    advance_to_next_due(&s, SUB_ID);
+    prepare_renewal(&s, 2);
+    s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).total_charged, AMOUNT * 2);
+}
+
+#[test]
+fn test_failed_renewal_does_not_count_toward_cap() {
+    let s = setup();
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).total_charged, 0);
+}
+
+#[test]
+fn test_spending_cap_enforced() {
+    let s = setup();
+    let sub_id = s.client.init_sub(
+        &s.owner,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &BillingInterval::Seconds(2_592_000),
+        &(AMOUNT * 2),
+        &None,
+        &None,
+    );
+
+    for cycle in 1..=3u64 {
+        advance_to_next_due(&s, sub_id);
+        s.client
+            .approve_renewal(&s.owner, &sub_id, &cycle, &AMOUNT, &1_000);
+        s.client.acquire_renewal_lock(&s.owner, &sub_id, &10);
+        let result = s.client.try_renew(&s.owner, &sub_id, &cycle, &AMOUNT);
+        if cycle < 3 {
+            assert_eq!(result, Ok(Ok(true)));
+        } else {
+            assert_eq!(result, Ok(Ok(false)));
+            assert!(published(&s, "spending_cap_exceeded"));
+        }
     }
+    assert_eq!(s.client.get_sub(&sub_id).total_charged, AMOUNT * 2);
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT * 2);
+    assert_eq!(s.client.get_renewal_lock(&sub_id), None);
+}
+
+/// Whether the last invocation published an event named `name` from the
+/// renewal contract.
+fn published(s: &Setup, name: &str) -> bool {
+    use soroban_sdk::testutils::Events as _;
+    use soroban_sdk::{Symbol, TryFromVal};
+
+    let name = Symbol::new(&s.env, name);
+    s.env.events().all().iter().any(|(contract, topics, _)| {
+        contract == s.client.address
+            && topics
+                .iter()
+                .any(|t| Symbol::try_from_val(&s.env, &t).is_ok_and(|t| t == name))
+    })
+}
+
+#[test]
+fn test_logging_failure_does_not_block_cancel() {
+    use subscription_logging::SubscriptionLoggingContract;
+
+    let s = setup();
+    // The renewal contract is never added as a writer, so every write fails.
+    let log_id = s.env.register(SubscriptionLoggingContract, ());
+    s.client.set_logging_contract(&log_id);
+
+    s.client.cancel_sub(&SUB_ID);
+
+    assert!(published(&s, "log_write_failed"));
+    assert_eq!(
+        s.client.get_sub(&SUB_ID).state,
+        SubscriptionState::Cancelled
+    );
+}
+
+#[test]
+fn test_renewal_writes_to_logging_contract() {
+    use subscription_logging::{
+        FailurePayload as LoggedFailure, LogEvent as LoggedEvent, LogPayload as LoggedPayload,
+        RenewalPayload as LoggedRenewal, SubscriptionLoggingContract,
+        SubscriptionLoggingContractClient,
+    };
+
+    let s = setup();
+    let log_id = s.env.register(SubscriptionLoggingContract, ());
+    let logs = SubscriptionLoggingContractClient::new(&s.env, &log_id);
+    logs.init(&Address::generate(&s.env));
+    logs.add_writer(&s.client.address);
+    s.client.set_logging_contract(&log_id);
+
+    let sub_id = s.client.init_sub(
+        &s.owner,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &BillingInterval::Seconds(2_592_000),
+        &10_000,
+        &None,
+        &Some(retry_policy(0, 0)),
+    );
+
+    s.client
+        .approve_renewal(&s.owner, &sub_id, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &sub_id, &10);
+    assert!(s.client.renew(&s.owner, &sub_id, &1, &AMOUNT));
+
+    advance_to_next_due(&s, sub_id);
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    s.client
+        .approve_renewal(&s.owner, &sub_id, &2, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &sub_id, &10);
+    assert!(!s.client.renew(&s.owner, &sub_id, &2, &AMOUNT));
+
+    s.client.cancel_sub(&sub_id);
 
-    pub fn get_subscription(env: Env, subscriber: Address) -> Subscription {
-        env.storage()
-            .instance()
-            .get(&DataKey::Subscription(subscriber))
-            .unwrap()
+    let entries = logs.get_logs(&sub_id);
+    let expected = [
+        LoggedEvent::Creation,
+        LoggedEvent::Approval,
+        LoggedEvent::Renewal,
+        LoggedEvent::Approval,
+        LoggedEvent::Failure,
+        LoggedEvent::Cancellation,
+    ];
+    assert_eq!(entries.len(), expected.len() as u32);
+    for (entry, event) in entries.iter().zip(expected) {
+        assert_eq!(entry.sub_id, sub_id);
+        assert_eq!(entry.event, event);
     }
+
+    assert_eq!(
+        entries.get(2).unwrap().payload,
+        LoggedPayload::Renewal(LoggedRenewal {
+            amount: AMOUNT,
+            cycle_id: 0,
+            ledger: s.env.ledger().sequence(),
+        })
+    );
+    assert_eq!(
+        entries.get(4).unwrap().payload,
+        LoggedPayload::Failure(LoggedFailure {
+            reason: FAILURE_REASON_PAYMENT_REJECTED,
+            failure_count: 1,
+        })
+    );
+    assert_eq!(logs.get_logs(&SUB_ID).len(), 0);
+}
+
+const HALF_CYCLE: u64 = 1_296_000;
+
+/// Approve, lock and renew `SUB_ID` for `amount`.
+fn renew_for(s: &Setup, approval_id: u64, amount: i128) -> bool {
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &approval_id, &amount, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    s.client.renew(&s.owner, &SUB_ID, &approval_id, &amount)
+}
+
+#[test]
+fn test_change_plan_requires_owner_and_merchant() {
+    let s = setup();
+    s.client.change_plan(
+        &SUB_ID,
+        &200,
+        &BillingInterval::Seconds(2_592_000),
+        &PlanChangeEffect::NextRenewal,
+    );
+
+    let auths = s.env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == s.owner));
+    assert!(auths.iter().any(|(addr, _)| *addr == s.merchant));
+}
+
+#[test]
+fn test_immediate_upgrade_charges_proration() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    s.env.ledger().with_mut(|li| li.timestamp += HALF_CYCLE);
+
+    s.client.change_plan(
+        &SUB_ID,
+        &300,
+        &BillingInterval::Seconds(2_592_000),
+        &PlanChangeEffect::Immediate,
+    );
+
+    // Half a cycle at the 200 difference.
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT + 100);
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.amount, 300);
+    assert_eq!(data.total_charged, AMOUNT + 100);
+
+    advance_to_next_due(&s, SUB_ID);
+    assert_eq!(s.client.get_due_amount(&SUB_ID), 300);
+    assert!(renew_for(&s, 2, 300));
+}
+
+#[test]
+fn test_immediate_upgrade_blocked_when_protocol_paused() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    s.env.ledger().with_mut(|li| li.timestamp += HALF_CYCLE);
+    s.client.set_paused(&true);
+
+    assert_eq!(
+        s.client.try_change_plan(
+            &SUB_ID,
+            &300,
+            &BillingInterval::Seconds(2_592_000),
+            &PlanChangeEffect::Immediate,
+        ),
+        Err(Ok(RenewalError::ProtocolPaused))
+    );
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+}
+
+#[test]
+fn test_immediate_downgrade_credits_next_renewal() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    s.env.ledger().with_mut(|li| li.timestamp += HALF_CYCLE);
+
+    s.client.change_plan(
+        &SUB_ID,
+        &50,
+        &BillingInterval::Seconds(2_592_000),
+        &PlanChangeEffect::Immediate,
+    );
+    assert_eq!(s.client.get_sub(&SUB_ID).credit, 25);
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+
+    advance_to_next_due(&s, SUB_ID);
+    assert_eq!(s.client.get_due_amount(&SUB_ID), 25);
+    assert!(renew_for(&s, 2, 25));
+
+    assert_eq!(s.client.get_sub(&SUB_ID).credit, 0);
+    advance_to_next_due(&s, SUB_ID);
+    assert_eq!(s.client.get_due_amount(&SUB_ID), 50);
+}
+
+#[test]
+fn test_immediate_change_without_paid_cycle_has_no_proration() {
+    let s = setup();
+    s.client.change_plan(
+        &SUB_ID,
+        &300,
+        &BillingInterval::Monthly(1),
+        &PlanChangeEffect::Immediate,
+    );
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.amount, 300);
+    assert_eq!(data.frequency, BillingInterval::Monthly(1));
+    assert_eq!(data.credit, 0);
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert!(renew_for(&s, 1, 300));
+}
+
+#[test]
+fn test_plan_change_at_next_renewal() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+
+    s.client.change_plan(
+        &SUB_ID,
+        &200,
+        &BillingInterval::Seconds(86_400),
+        &PlanChangeEffect::NextRenewal,
+    );
+    assert_eq!(s.client.get_sub(&SUB_ID).amount, AMOUNT);
+    assert!(s.client.get_pending_plan(&SUB_ID).is_some());
+
+    advance_to_next_due(&s, SUB_ID);
+    let due = s.client.get_sub(&SUB_ID).next_due;
+    assert_eq!(s.client.get_due_amount(&SUB_ID), 200);
+    assert!(renew_for(&s, 2, 200));
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.amount, 200);
+    assert_eq!(data.next_due, due + 86_400);
+    assert_eq!(s.client.get_pending_plan(&SUB_ID), None);
+
+    advance_to_next_due(&s, SUB_ID);
+    assert!(renew_for(&s, 3, 200));
+}
+
+#[test]
+fn test_change_plan_rejected_after_cancel() {
+    let s = setup();
+    s.client.cancel_sub(&SUB_ID);
+    assert_eq!(
+        s.client.try_change_plan(
+            &SUB_ID,
+            &200,
+            &BillingInterval::Seconds(2_592_000),
+            &PlanChangeEffect::Immediate,
+        ),
+        Err(Ok(RenewalError::NotActive))
+    );
+}
+
+const PRICE_DEADLINE: u64 = 7 * 86_400;
+
+fn propose_price_change(s: &Setup, new_amount: i128) {
+    let deadline = s.env.ledger().timestamp() + PRICE_DEADLINE;
+    s.client
+        .propose_price_change(&SUB_ID, &new_amount, &deadline);
+}
+
+#[test]
+fn test_accepted_price_change_applies_next_cycle() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    propose_price_change(&s, 150);
+
+    let auths = s.env.auths();
+    assert!(auths.iter().any(|(addr, _)| *addr == s.merchant));
+    assert_eq!(s.client.get_price_change(&SUB_ID).unwrap().new_amount, 150);
+
+    s.client.accept_price_change(&SUB_ID);
+    assert_eq!(s.client.get_price_change(&SUB_ID), None);
+    assert_eq!(s.client.get_sub(&SUB_ID).amount, AMOUNT);
+
+    advance_to_next_due(&s, SUB_ID);
+    assert_eq!(s.client.get_due_amount(&SUB_ID), 150);
+    assert!(renew_for(&s, 2, 150));
+    assert_eq!(s.client.get_sub(&SUB_ID).amount, 150);
+}
+
+#[test]
+fn test_accepted_price_change_keeps_scheduled_frequency() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    s.client.change_plan(
+        &SUB_ID,
+        &200,
+        &BillingInterval::Seconds(86_400),
+        &PlanChangeEffect::NextRenewal,
+    );
+    propose_price_change(&s, 150);
+    s.client.accept_price_change(&SUB_ID);
+
+    assert_eq!(
+        s.client.get_pending_plan(&SUB_ID),
+        Some(PendingPlan {
+            amount: 150,
+            frequency: BillingInterval::Seconds(86_400),
+        })
+    );
+
+    advance_to_next_due(&s, SUB_ID);
+    let due = s.client.get_sub(&SUB_ID).next_due;
+    assert!(renew_for(&s, 2, 150));
+    assert_eq!(s.client.get_sub(&SUB_ID).next_due, due + 86_400);
+}
+
+#[test]
+fn test_renewal_at_current_price_while_proposal_pending() {
+    let s = setup();
+    propose_price_change(&s, 150);
+    assert!(renew_for(&s, 1, AMOUNT));
+}
+
+#[test]
+fn test_accept_price_change_after_deadline() {
+    let s = setup();
+    propose_price_change(&s, 150);
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PRICE_DEADLINE + 1);
+    assert_eq!(
+        s.client.try_accept_price_change(&SUB_ID),
+        Err(Ok(RenewalError::PriceChangeDeadlinePassed))
+    );
+}
+
+#[test]
+fn test_renew_blocked_after_unanswered_price_change() {
+    let s = setup();
+    propose_price_change(&s, 150);
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PRICE_DEADLINE + 1);
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::PriceChangeConsentExpired))
+    );
+}
+
+#[test]
+fn test_lapsed_price_change_pauses_by_default() {
+    let s = setup();
+    propose_price_change(&s, 150);
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PRICE_DEADLINE + 1);
+    s.client.expire_price_change(&SUB_ID);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Paused);
+    assert_eq!(s.client.get_sub(&SUB_ID).amount, AMOUNT);
+    assert_eq!(s.client.get_price_change(&SUB_ID), None);
+}
+
+#[test]
+fn test_lapsed_price_change_cancels_when_preset() {
+    let s = setup();
+    s.client
+        .set_price_change_policy(&SUB_ID, &PriceChangePolicy::Cancel);
+    propose_price_change(&s, 150);
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PRICE_DEADLINE + 1);
+    s.client.expire_price_change(&SUB_ID);
+
+    assert_eq!(
+        s.client.get_sub(&SUB_ID).state,
+        SubscriptionState::Cancelled
+    );
+    assert_eq!(
+        s.client.get_lifecycle(&SUB_ID).canceled_at,
+        s.env.ledger().timestamp()
+    );
+}
+
+#[test]
+fn test_expire_price_change_before_deadline() {
+    let s = setup();
+    propose_price_change(&s, 150);
+    assert_eq!(
+        s.client.try_expire_price_change(&SUB_ID),
+        Err(Ok(RenewalError::PriceChangeDeadlineNotReached))
+    );
+}
+
+#[test]
+fn test_pause_and_resume_shifts_schedule() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    let due = s.client.get_sub(&SUB_ID).next_due;
+
+    s.client.pause_sub(&SUB_ID, &None);
+    let paused_at = s.env.ledger().timestamp();
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Paused);
+    assert_eq!(s.client.get_lifecycle(&SUB_ID).paused_at, paused_at);
+
+    s.env.ledger().with_mut(|li| li.timestamp += HALF_CYCLE);
+    s.client.resume_sub(&SUB_ID);
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Active);
+    assert_eq!(data.next_due, due + HALF_CYCLE);
+    assert_eq!(
+        s.client.get_lifecycle(&SUB_ID).resumed_at,
+        paused_at + HALF_CYCLE
+    );
+    assert_eq!(s.client.get_pause(&SUB_ID), None);
+}
+
+#[test]
+fn test_renew_blocked_while_paused() {
+    let s = setup();
+    s.client.pause_sub(&SUB_ID, &None);
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::SubscriptionPaused))
+    );
+}
+
+#[test]
+fn test_resume_restores_trial() {
+    let s = setup();
+    init_trial_sub(&s);
+    s.client.pause_sub(&TRIAL_SUB_ID, &None);
+    s.client.resume_sub(&TRIAL_SUB_ID);
+    assert_eq!(
+        s.client.get_sub(&TRIAL_SUB_ID).state,
+        SubscriptionState::Trialing
+    );
+}
+
+#[test]
+fn test_scheduled_resume_open_to_anyone() {
+    let s = setup();
+    let resume_at = s.env.ledger().timestamp() + HALF_CYCLE;
+    s.client.pause_sub(&SUB_ID, &Some(resume_at));
+
+    s.env.ledger().with_mut(|li| li.timestamp = resume_at);
+    s.env.set_auths(&[]);
+    s.client.resume_sub(&SUB_ID);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Active);
+}
+
+#[test]
+fn test_early_resume_requires_owner() {
+    let s = setup();
+    let resume_at = s.env.ledger().timestamp() + HALF_CYCLE;
+    s.client.pause_sub(&SUB_ID, &Some(resume_at));
+
+    s.env.set_auths(&[]);
+    assert!(s.client.try_resume_sub(&SUB_ID).is_err());
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Paused);
+}
+
+#[test]
+fn test_pause_twice_rejected() {
+    let s = setup();
+    s.client.pause_sub(&SUB_ID, &None);
+    assert_eq!(
+        s.client.try_pause_sub(&SUB_ID, &None),
+        Err(Ok(RenewalError::AlreadyPaused))
+    );
+}
+
+/// Drive `SUB_ID` into `Failed` with a rejected transfer, then restore the
+/// allowance.
+fn fail_sub(s: &Setup) {
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Failed);
+    s.token
+        .approve(&s.owner, &s.client.address, &10_000, &1_000);
+}
+
+#[test]
+fn test_reactivate_settles_missed_cycle() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    fail_sub(&s);
+    let due = s.client.get_sub(&SUB_ID).next_due;
+
+    s.client.reactivate_sub(&SUB_ID, &true);
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Active);
+    assert_eq!(data.failure_count, 0);
+    assert_eq!(data.next_due, due + 2_592_000);
+    assert_eq!(data.total_charged, AMOUNT);
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+    assert_eq!(
+        s.client.get_lifecycle(&SUB_ID).activated_at,
+        s.env.ledger().timestamp()
+    );
+}
+
+#[test]
+fn test_reactivate_blocked_when_protocol_paused() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    fail_sub(&s);
+    s.client.set_paused(&true);
+
+    assert_eq!(
+        s.client.try_reactivate_sub(&SUB_ID, &true),
+        Err(Ok(RenewalError::ProtocolPaused))
+    );
+    assert_eq!(s.token.balance(&s.merchant), 0);
+}
+
+#[test]
+fn test_reactivate_settles_at_accepted_price() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    propose_price_change(&s, 150);
+    fail_sub(&s);
+    s.client.accept_price_change(&SUB_ID);
+
+    s.client.reactivate_sub(&SUB_ID, &true);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).amount, 150);
+    assert_eq!(s.token.balance(&s.merchant), 150);
+}
+
+#[test]
+fn test_reactivate_rejects_unanswered_price_change() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    propose_price_change(&s, 150);
+    fail_sub(&s);
+    s.env
+        .ledger()
+        .with_mut(|li| li.timestamp += PRICE_DEADLINE + 1);
+
+    assert_eq!(
+        s.client.try_reactivate_sub(&SUB_ID, &true),
+        Err(Ok(RenewalError::PriceChangeConsentExpired))
+    );
+}
+
+#[test]
+fn test_reactivate_settles_every_missed_cycle() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    fail_sub(&s);
+    s.env.ledger().with_mut(|li| li.timestamp += 2 * 2_592_000);
+
+    s.client.reactivate_sub(&SUB_ID, &true);
+
+    assert_eq!(s.token.balance(&s.merchant), 3 * AMOUNT);
+    assert!(s.client.get_sub(&SUB_ID).next_due > s.env.ledger().timestamp());
+}
+
+#[test]
+fn test_reactivate_without_settling_restarts_billing() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    fail_sub(&s);
+    s.env.ledger().with_mut(|li| li.timestamp += HALF_CYCLE);
+
+    s.client.reactivate_sub(&SUB_ID, &false);
+
+    let data = s.client.get_sub(&SUB_ID);
+    assert_eq!(data.state, SubscriptionState::Active);
+    assert_eq!(data.next_due, s.env.ledger().timestamp());
+    assert_eq!(s.token.balance(&s.merchant), 0);
+    assert!(renew_for(&s, 2, AMOUNT));
+}
+
+#[test]
+fn test_reactivate_rejects_unpaid_settlement() {
+    let s = setup_with_policy(retry_policy(0, 0));
+    fail_sub(&s);
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    assert_eq!(
+        s.client.try_reactivate_sub(&SUB_ID, &true),
+        Err(Ok(RenewalError::PaymentFailed))
+    );
+}
+
+#[test]
+fn test_reactivate_requires_failed_state() {
+    let s = setup();
+    assert_eq!(
+        s.client.try_reactivate_sub(&SUB_ID, &true),
+        Err(Ok(RenewalError::NotFailed))
+    );
+}
+
+#[test]
+fn test_retry_policy_defaults_to_admin_setting() {
+    let s = setup();
+    assert_eq!(s.client.get_default_retry_policy(), retry_policy(3, 0));
+
+    s.client.set_default_retry_policy(&retry_policy(5, 100));
+    let sub_id = s.client.init_sub(
+        &s.owner,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &BillingInterval::Seconds(2_592_000),
+        &10_000,
+        &None,
+        &None,
+    );
+    assert_eq!(s.client.get_sub(&sub_id).retry_policy, retry_policy(5, 100));
+}
+
+#[test]
+fn test_cooldown_backs_off_exponentially() {
+    let s = setup_with_policy(RetryPolicy {
+        max_retries: 5,
+        cooldown_ledgers: 10,
+        backoff_factor: 2,
+        grace_period: 0,
+    });
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+
+    prepare_renewal(&s, 1);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+
+    s.env.ledger().with_mut(|li| li.sequence_number += 10);
+    prepare_renewal(&s, 2);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT));
+
+    // Second failure doubles the wait to 20 ledgers.
+    s.env.ledger().with_mut(|li| li.sequence_number += 10);
+    prepare_renewal(&s, 3);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &3, &AMOUNT),
+        Err(Ok(RenewalError::CooldownActive))
+    );
+
+    s.env.ledger().with_mut(|li| li.sequence_number += 10);
+    prepare_renewal(&s, 4);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &4, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).failure_count, 3);
+}
+
+#[test]
+fn test_grace_period_delays_failure() {
+    let s = setup_with_policy(RetryPolicy {
+        max_retries: 0,
+        cooldown_ledgers: 0,
+        backoff_factor: 1,
+        grace_period: 86_400,
+    });
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Grace);
+
+    s.env.ledger().with_mut(|li| li.timestamp += 86_400);
+    prepare_renewal(&s, 2);
+    s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Failed);
+}
+
+#[test]
+fn test_integrity_violation_on_retry_policy_change() {
+    let s = setup();
+    s.env.as_contract(&s.client.address, || {
+        let mut data: SubscriptionData = s.env.storage().persistent().get(&SUB_ID).unwrap();
+        data.retry_policy.max_retries = u32::MAX;
+        s.env.storage().persistent().set(&SUB_ID, &data);
+    });
+
+    prepare_renewal(&s, 1);
+    assert_eq!(
+        s.client.try_renew(&s.owner, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::IntegrityViolation))
+    );
+}
+
+#[test]
+fn test_zero_backoff_factor_rejected() {
+    let s = setup();
+    assert_eq!(
+        s.client.try_set_default_retry_policy(&RetryPolicy {
+            max_retries: 3,
+            cooldown_ledgers: 10,
+            backoff_factor: 0,
+            grace_period: 0,
+        }),
+        Err(Ok(RenewalError::InvalidRetryPolicy))
+    );
+}
+
+const GRACE: u64 = 3 * 86_400;
+
+fn setup_with_grace<'a>(max_retries: u32) -> Setup<'a> {
+    let s = setup_with_policy(RetryPolicy {
+        max_retries,
+        cooldown_ledgers: 0,
+        backoff_factor: 1,
+        grace_period: GRACE,
+    });
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+    s
+}
+
+#[test]
+fn test_failed_payment_enters_grace_and_keeps_access() {
+    let s = setup_with_grace(3);
+    assert!(s.client.is_entitled(&SUB_ID));
+
+    prepare_renewal(&s, 1);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Grace);
+    assert!(s.client.is_entitled(&SUB_ID));
+
+    s.env.ledger().with_mut(|li| li.timestamp += GRACE);
+    assert!(!s.client.is_entitled(&SUB_ID));
+}
+
+#[test]
+fn test_unbounded_grace_period_does_not_overflow() {
+    let s = setup_with_policy(RetryPolicy {
+        max_retries: 0,
+        cooldown_ledgers: 0,
+        backoff_factor: 1,
+        grace_period: u64::MAX,
+    });
+    s.token.approve(&s.owner, &s.client.address, &0, &1_000);
+
+    prepare_renewal(&s, 1);
+    assert!(!s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Grace);
+    assert!(s.client.is_entitled(&SUB_ID));
+    assert_eq!(
+        s.client.try_end_grace(&SUB_ID),
+        Err(Ok(RenewalError::GraceNotOver))
+    );
+}
+
+#[test]
+fn test_failure_after_grace_moves_to_retrying() {
+    let s = setup_with_grace(3);
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    s.env.ledger().with_mut(|li| li.timestamp += GRACE);
+    prepare_renewal(&s, 2);
+    s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT);
+
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Retrying);
+    assert!(!s.client.is_entitled(&SUB_ID));
+}
+
+#[test]
+fn test_payment_during_grace_restores_active() {
+    let s = setup_with_grace(3);
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+
+    s.token
+        .approve(&s.owner, &s.client.address, &10_000, &1_000);
+    prepare_renewal(&s, 2);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &2, &AMOUNT));
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Active);
+    assert!(s.client.is_entitled(&SUB_ID));
+}
+
+#[test]
+fn test_end_grace_fails_exhausted_subscription() {
+    let s = setup_with_grace(0);
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Grace);
+
+    s.env.ledger().with_mut(|li| li.timestamp += GRACE);
+    s.client.end_grace(&SUB_ID);
+    assert_eq!(s.client.get_sub(&SUB_ID).state, SubscriptionState::Failed);
+}
+
+#[test]
+fn test_end_grace_before_deadline() {
+    let s = setup_with_grace(0);
+    prepare_renewal(&s, 1);
+    s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT);
+    assert_eq!(
+        s.client.try_end_grace(&SUB_ID),
+        Err(Ok(RenewalError::GraceNotOver))
+    );
+}
+
+#[test]
+fn test_entitlement_by_state() {
+    let s = setup();
+    assert!(renew_for(&s, 1, AMOUNT));
+    assert!(s.client.is_entitled(&SUB_ID));
+
+    s.client.pause_sub(&SUB_ID, &None);
+    assert!(!s.client.is_entitled(&SUB_ID));
+    s.client.resume_sub(&SUB_ID);
+
+    s.client.cancel_sub(&SUB_ID);
+    assert!(s.client.is_entitled(&SUB_ID));
+    advance_to_next_due(&s, SUB_ID);
+    assert!(!s.client.is_entitled(&SUB_ID));
+}
+
+#[test]
+fn test_init_sub_assigns_sequential_ids() {
+    let s = setup();
+    let create = || {
+        s.client.init_sub(
+            &s.owner,
+            &s.merchant,
+            &s.token.address,
+            &AMOUNT,
+            &BillingInterval::Seconds(2_592_000),
+            &10_000,
+            &None,
+            &None,
+        )
+    };
+    assert_eq!(create(), SUB_ID + 1);
+    assert_eq!(create(), SUB_ID + 2);
+}
+
+#[test]
+fn test_init_sub_requires_owner_auth() {
+    let s = setup();
+    s.env.set_auths(&[]);
+    let result = s.client.try_init_sub(
+        &s.owner,
+        &s.merchant,
+        &s.token.address,
+        &AMOUNT,
+        &BillingInterval::Seconds(2_592_000),
+        &10_000,
+        &None,
+        &None,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_stranger_cannot_hijack_existing_subscription() {
+    let s = setup();
+    let before = s.client.get_sub(&SUB_ID);
+
+    let stranger = Address::generate(&s.env);
+    let stranger_id = s.client.init_sub(
+        &stranger,
+        &stranger,
+        &s.token.address,
+        &1,
+        &BillingInterval::Seconds(60),
+        &1,
+        &None,
+        &None,
+    );
+
+    assert_ne!(stranger_id, SUB_ID);
+    assert_eq!(s.client.get_sub(&SUB_ID), before);
+    assert_eq!(s.client.get_sub(&stranger_id).owner, stranger);
+}
+
+/// Deploy an agent registry, point the renewal contract at it and return a
+/// client for it.
+fn setup_agent_registry<'a>(s: &'a Setup<'a>) -> agent_registry::AgentRegistryClient<'a> {
+    use agent_registry::{AgentRegistry, AgentRegistryClient};
+
+    let registry_id = s.env.register(AgentRegistry, ());
+    let registry = AgentRegistryClient::new(&s.env, &registry_id);
+    registry.init(&Address::generate(&s.env));
+    s.client.set_agent_registry(&registry_id);
+    registry
+}
+
+/// Register a new agent holding `scopes` and let it act on `SUB_ID`.
+fn register_agent(
+    s: &Setup,
+    registry: &agent_registry::AgentRegistryClient,
+    scopes: u32,
+) -> Address {
+    let agent = Address::generate(&registry.env);
+    registry.register(&agent, &scopes);
+    s.client.allow_agent(&SUB_ID, &agent);
+    agent
+}
+
+#[test]
+fn test_agent_requires_owner_opt_in() {
+    use agent_registry::Scope;
+
+    let s = setup();
+    let registry = setup_agent_registry(&s);
+    let agent = Address::generate(&s.env);
+    registry.register(&agent, &(Scope::Renewals as u32 | Scope::Approvals as u32));
+
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&agent, &SUB_ID, &10),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+    assert_eq!(
+        s.client
+            .try_approve_renewal(&agent, &SUB_ID, &1, &AMOUNT, &1_000),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+
+    s.client.allow_agent(&SUB_ID, &agent);
+    assert_eq!(
+        s.client.get_allowed_agents(&SUB_ID),
+        soroban_sdk::vec![&s.env, agent.clone()]
+    );
+    s.client
+        .approve_renewal(&agent, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&agent, &SUB_ID, &10);
+    assert!(s.client.renew(&agent, &SUB_ID, &1, &AMOUNT));
+
+    s.client.disallow_agent(&SUB_ID, &agent);
+    assert_eq!(
+        s.client
+            .try_approve_renewal(&agent, &SUB_ID, &2, &AMOUNT, &1_000),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+    assert_eq!(
+        s.client.try_disallow_agent(&SUB_ID, &agent),
+        Err(Ok(RenewalError::AgentNotAllowed))
+    );
+}
+
+#[test]
+fn test_allowed_agents_are_bounded() {
+    let s = setup();
+    for _ in 0..MAX_ALLOWED_AGENTS {
+        s.client.allow_agent(&SUB_ID, &Address::generate(&s.env));
+    }
+    assert_eq!(
+        s.client
+            .try_allow_agent(&SUB_ID, &Address::generate(&s.env)),
+        Err(Ok(RenewalError::TooManyAgents))
+    );
+}
+
+#[test]
+fn test_renewals_agent_can_renew() {
+    let s = setup();
+    let registry = setup_agent_registry(&s);
+    let agent = register_agent(&s, &registry, agent_registry::Scope::Renewals as u32);
+
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&agent, &SUB_ID, &10);
+    assert!(s.client.renew(&agent, &SUB_ID, &1, &AMOUNT));
+    assert_eq!(s.token.balance(&s.merchant), AMOUNT);
+}
+
+#[test]
+fn test_agent_rejected_without_registry() {
+    use agent_registry::{AgentRegistry, AgentRegistryClient, Scope};
+
+    let s = setup();
+    let registry_id = s.env.register(AgentRegistry, ());
+    let registry = AgentRegistryClient::new(&s.env, &registry_id);
+    registry.init(&Address::generate(&s.env));
+    let agent = register_agent(&s, &registry, Scope::Renewals as u32);
+
+    assert_eq!(s.client.get_agent_registry(), None);
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&agent, &SUB_ID, &10),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_revoked_agent_cannot_renew() {
+    let s = setup();
+    let registry = setup_agent_registry(&s);
+    let agent = register_agent(&s, &registry, agent_registry::Scope::Renewals as u32);
+
+    s.client
+        .approve_renewal(&s.owner, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&agent, &SUB_ID, &10);
+    registry.revoke_agent(&agent);
+
+    assert_eq!(
+        s.client.try_renew(&agent, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+    assert_eq!(s.token.balance(&s.merchant), 0);
+}
+
+#[test]
+fn test_mis_scoped_agent_cannot_renew() {
+    let s = setup();
+    let registry = setup_agent_registry(&s);
+    let agent = register_agent(&s, &registry, agent_registry::Scope::GiftCards as u32);
+
+    assert_eq!(
+        s.client.try_acquire_renewal_lock(&agent, &SUB_ID, &10),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+    assert_eq!(
+        s.client.try_renew(&agent, &SUB_ID, &1, &AMOUNT),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+}
+
+#[test]
+fn test_approvals_agent_can_approve() {
+    let s = setup();
+    let registry = setup_agent_registry(&s);
+    let agent = register_agent(&s, &registry, agent_registry::Scope::Approvals as u32);
+
+    s.client
+        .approve_renewal(&agent, &SUB_ID, &1, &AMOUNT, &1_000);
+    s.client.acquire_renewal_lock(&s.owner, &SUB_ID, &10);
+    assert!(s.client.renew(&s.owner, &SUB_ID, &1, &AMOUNT));
+}
+
+#[test]
+fn test_approval_requires_approvals_scope() {
+    let s = setup();
+    let registry = setup_agent_registry(&s);
+    let renewals_agent = register_agent(&s, &registry, agent_registry::Scope::Renewals as u32);
+    assert_eq!(
+        s.client
+            .try_approve_renewal(&renewals_agent, &SUB_ID, &1, &AMOUNT, &1_000),
+        Err(Ok(RenewalError::Unauthorized))
+    );
+
+    let approvals_agent = register_agent(&s, &registry, agent_registry::Scope::Approvals as u32);
+    registry.revoke_agent(&approvals_agent);
+    assert_eq!(
+        s.client
+            .try_approve_renewal(&approvals_agent, &SUB_ID, &1, &AMOUNT, &1_000),
+        Err(Ok(RenewalError::Unauthorized))
+    );
 }
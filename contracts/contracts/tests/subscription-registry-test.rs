@@ -1,5 +1,5 @@
 use soroban_sdk::{testutils::Address as _, Address, BytesN, Env, String};
-use subscription_registry::{SubscriptionRegistry, SubscriptionRegistryClient};
+use subscription_registry::{BillingInterval, SubscriptionRegistry, SubscriptionRegistryClient};
 
 #[test]
 fn test_create_subscription() {
@@ -11,7 +11,7 @@ fn test_create_subscription() {
 
     let user = Address::generate(&env);
     let service_id = String::from_str(&env, "netflix");
-    let billing_interval = 2592000u64;
+    let billing_interval = BillingInterval::Seconds(2592000);
     let expected_amount = 1599i128;
     let next_renewal = 1735689600u64;
 
@@ -50,7 +50,7 @@ fn test_create_multiple_subscriptions() {
     let sub1_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -58,7 +58,7 @@ fn test_create_multiple_subscriptions() {
     let sub2_id = client.create_subscription(
         &user,
         &String::from_str(&env, "spotify"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &999i128,
         &1735689600u64,
     );
@@ -66,7 +66,7 @@ fn test_create_multiple_subscriptions() {
     let sub3_id = client.create_subscription(
         &user,
         &String::from_str(&env, "hulu"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &799i128,
         &1735689600u64,
     );
@@ -91,7 +91,7 @@ fn test_update_subscription() {
     let subscription_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -126,7 +126,7 @@ fn test_cancel_subscription() {
     let subscription_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -139,7 +139,28 @@ fn test_cancel_subscription() {
 }
 
 #[test]
-#[should_panic(expected = "billing_interval must be greater than 0")]
+fn test_create_subscription_with_calendar_interval() {
+    // Test that calendar-based billing intervals are stored as given
+    let env = Env::default();
+    env.mock_all_auths();
+    let contract_id = env.register(SubscriptionRegistry, ());
+    let client = SubscriptionRegistryClient::new(&env, &contract_id);
+
+    let user = Address::generate(&env);
+    let subscription_id = client.create_subscription(
+        &user,
+        &String::from_str(&env, "netflix"),
+        &BillingInterval::Monthly(31),
+        &1599i128,
+        &1735689600u64,
+    );
+
+    let metadata = client.get_subscription(&subscription_id).unwrap();
+    assert_eq!(metadata.billing_interval, BillingInterval::Monthly(31));
+}
+
+#[test]
+#[should_panic(expected = "invalid billing_interval")]
 fn test_create_subscription_invalid_billing_interval() {
     // Test validation: billing interval cannot be zero
     let env = Env::default();
@@ -151,7 +172,7 @@ fn test_create_subscription_invalid_billing_interval() {
     client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &0u64,
+        &BillingInterval::Seconds(0),
         &1599i128,
         &1735689600u64,
     );
@@ -170,7 +191,7 @@ fn test_create_subscription_negative_amount() {
     client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &-100i128,
         &1735689600u64,
     );
@@ -204,7 +225,7 @@ fn test_cancel_already_cancelled_subscription() {
     let subscription_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -226,7 +247,7 @@ fn test_update_cancelled_subscription() {
     let subscription_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -269,7 +290,7 @@ fn test_multiple_users_independent() {
     let sub1_id = client.create_subscription(
         &user1,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -277,7 +298,7 @@ fn test_multiple_users_independent() {
     let sub2_id = client.create_subscription(
         &user2,
         &String::from_str(&env, "spotify"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &999i128,
         &1735689600u64,
     );
@@ -305,21 +326,21 @@ fn test_subscription_id_uniqueness() {
     let sub1_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
     let sub2_id = client.create_subscription(
         &user,
         &String::from_str(&env, "spotify"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &999i128,
         &1735689600u64,
     );
     let sub3_id = client.create_subscription(
         &user,
         &String::from_str(&env, "hulu"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &799i128,
         &1735689600u64,
     );
@@ -344,7 +365,7 @@ fn test_create_duplicate_subscription_fails() {
     client.create_subscription(
         &user,
         &service_id,
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -353,7 +374,7 @@ fn test_create_duplicate_subscription_fails() {
     client.create_subscription(
         &user,
         &service_id,
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );
@@ -371,7 +392,7 @@ fn test_update_subscription_unauthorized() {
     let subscription_id = client.create_subscription(
         &user,
         &String::from_str(&env, "netflix"),
-        &2592000u64,
+        &BillingInterval::Seconds(2592000),
         &1599i128,
         &1735689600u64,
     );